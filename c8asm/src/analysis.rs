@@ -0,0 +1,133 @@
+use c8common::asm::ROM;
+use c8common::{Address, Instruction, RawInstruction, NUMBER_OF_ADDRESSES};
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ByteKind {
+    Code,
+    Data,
+}
+
+/// Classification of every byte in a `ROM` as either reachable code or data
+#[derive(Debug, Clone)]
+pub struct CodeMap {
+    kinds: Vec<ByteKind>,
+    indirect_jumps: Vec<Address>,
+}
+
+impl CodeMap {
+    /// Follows every statically known path through the program, starting at `PROGRAM_START`
+    pub fn analyse(rom: &ROM) -> Self {
+        let mut kinds = vec![ByteKind::Data; NUMBER_OF_ADDRESSES - Address::PROGRAM_START_INDEX];
+        let mut indirect_jumps = vec![];
+        let mut pending = vec![Address::PROGRAM_START];
+
+        while let Some(addr) = pending.pop() {
+            let Some(offset) = Self::offset(addr) else {
+                continue;
+            };
+            if offset + 1 >= kinds.len() || kinds[offset] == ByteKind::Code {
+                continue;
+            }
+            let raw = RawInstruction::from((
+                rom[Address::new(offset as u16)],
+                rom[Address::new(offset as u16 + 1)],
+            ));
+            let instruction = match Instruction::try_from_data(raw) {
+                Ok(instruction) => instruction,
+                Err(_) => continue,
+            };
+            kinds[offset] = ByteKind::Code;
+            kinds[offset + 1] = ByteKind::Code;
+
+            // Nothing follows the last instruction in memory
+            let after = |bytes: u16| Address::try_from(addr.as_u16() + bytes).ok();
+            if !instruction.is_control_flow() {
+                pending.extend(after(2));
+                continue;
+            }
+            pending.extend(instruction.jump_target());
+            if let Instruction::JumpRelative(_) = instruction {
+                indirect_jumps.push(addr);
+            }
+            if !instruction.is_terminating() {
+                pending.extend(after(2));
+            }
+            if instruction.is_skip() {
+                pending.extend(after(4));
+            }
+        }
+
+        Self {
+            kinds,
+            indirect_jumps,
+        }
+    }
+
+    fn offset(addr: Address) -> Option<usize> {
+        usize::from(addr).checked_sub(Address::PROGRAM_START_INDEX)
+    }
+
+    /// Addresses below `PROGRAM_START` are never part of the ROM, so are reported as data
+    pub fn kind_at(&self, addr: Address) -> ByteKind {
+        Self::offset(addr)
+            .and_then(|offset| self.kinds.get(offset).copied())
+            .unwrap_or(ByteKind::Data)
+    }
+
+    pub fn is_code(&self, addr: Address) -> bool {
+        self.kind_at(addr) == ByteKind::Code
+    }
+
+    /// Locations of `Bnnn` jumps, whose targets can't be known statically
+    /// Code only reachable through these will have been classified as data
+    pub fn indirect_jumps(&self) -> &[Address] {
+        &self.indirect_jumps
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compilation::compile;
+    use crate::instruction_sets::Chip8InstructionSet;
+    use crate::parsing::parse;
+    use crate::tokenizing::tokenize;
+
+    #[test]
+    fn test_sprite_table_is_data() {
+        let source = "
+            ld i, sprite
+            call draw
+            loop:
+            jp loop
+            sprite:
+            .data 0xF0, 0x90, 0xF0
+            draw:
+            drw v0, v1, 3
+            ret
+        ";
+        let rom =
+            compile::<Chip8InstructionSet>(parse(tokenize(source).unwrap()).unwrap()).unwrap();
+        let map = CodeMap::analyse(&rom);
+
+        for addr in 0x200..0x206 {
+            assert!(map.is_code(Address::new(addr)), "0x{:03X} is code", addr);
+        }
+        for addr in 0x206..0x209 {
+            assert!(!map.is_code(Address::new(addr)), "0x{:03X} is data", addr);
+        }
+        for addr in 0x209..0x20D {
+            assert!(map.is_code(Address::new(addr)), "0x{:03X} is code", addr);
+        }
+        assert!(!map.is_code(Address::new(0x20D)));
+        assert!(map.indirect_jumps().is_empty());
+    }
+
+    #[test]
+    fn test_runs_to_end_of_memory() {
+        // All zeroes decode as `Nop`, right up to the last address
+        let map = CodeMap::analyse(&ROM::new());
+        assert!(map.is_code(Address::PROGRAM_START));
+        assert!(map.is_code(Address::MAX));
+    }
+}
@@ -2,22 +2,34 @@ use std::error::Error;
 use clap::Parser;
 use log::{info, LevelFilter};
 use simplelog::{ColorChoice, ConfigBuilder, TerminalMode, TermLogger};
-use c8asm::compilation::compile;
-use c8asm::instruction_sets::Chip8InstructionSet;
-use c8asm::parsing::parse;
-use c8asm::tokenizing::tokenize;
+use c8asm::compilation::Target;
+use c8asm::{assemble_with, read_source, reference_mismatches, STDIO_PATH};
+use c8common::asm::ROM;
+use std::io::Write;
 use std::str::FromStr;
 
 #[derive(Parser, Debug)]
 struct Args {
+    /// The source to assemble, or `-` for stdin
     asm_path: String,
-    out_path: String,
+    /// Where to write the ROM, or `-` for stdout
+    #[arg(required_unless_present = "expect")]
+    out_path: Option<String>,
     #[arg(long = "log", value_parser = <LevelFilter as FromStr>::from_str, default_value_t = LevelFilter::Info)]
     log_level: LevelFilter,
+    /// Write an annotated hex dump instead of raw bytes
+    #[arg(long = "hex")]
+    hex: bool,
+    /// Compare against a reference ROM instead of writing anything, failing on any difference
+    #[arg(long = "expect")]
+    expect: Option<String>,
+    /// The instruction set to accept: chip8, schip or xochip
+    #[arg(long = "target", value_parser = <Target as FromStr>::from_str, default_value = "chip8")]
+    target: Target,
 }
 
 fn main() -> Result<(), Box<dyn Error>> {
-    let Args { asm_path, out_path, log_level } = Args::parse();
+    let Args { asm_path, out_path, log_level, hex, expect, target } = Args::parse();
 
     TermLogger::init(
         log_level,
@@ -43,15 +55,36 @@ fn main() -> Result<(), Box<dyn Error>> {
         )
     }))?;
 
-    let contents = std::fs::read_to_string(asm_path)?;
+    let (contents, base_dir) = read_source(&asm_path, std::io::stdin())?;
     info!("Read file contents");
-    let tokens = tokenize(&contents).map_err(|error| miette::Error::new(error).with_source_code(contents.clone()))?;
-    info!("Tokenized");
-    let parts = parse(tokens).map_err(|error| miette::Error::new(error).with_source_code(contents.clone()))?;
-    info!("Parsed");
-    let rom = compile::<Chip8InstructionSet>(parts).map_err(|error| miette::Error::new(error).with_source_code(contents))?;
-    info!("Compiled");
-    rom.save(out_path)?;
+    let rom = assemble_with(&contents, Some(&base_dir), target).map_err(miette::Error::new)?;
+    info!("Assembled");
+    if let Some(reference) = expect {
+        let reference = ROM::from_file(reference).map_err(|e| format!("{:?}", e))?;
+        let mismatches = reference_mismatches(&rom, &reference);
+        for mismatch in &mismatches {
+            eprintln!("{}", mismatch);
+        }
+        if !mismatches.is_empty() {
+            std::process::exit(1);
+        }
+        info!("Matches reference, OK");
+        return Ok(());
+    }
+    let out_path = out_path.expect("required without --expect");
+    if out_path == STDIO_PATH {
+        let mut stdout = std::io::stdout().lock();
+        if hex {
+            stdout.write_all(rom.to_hex_dump().as_bytes())?;
+        } else {
+            rom.write_to(&mut stdout)?;
+        }
+        stdout.flush()?;
+    } else if hex {
+        std::fs::write(out_path, rom.to_hex_dump())?;
+    } else {
+        rom.save(out_path)?;
+    }
     info!("Saved, OK");
     Ok(())
 }
@@ -0,0 +1,50 @@
+use c8common::asm::ROM;
+use c8common::memory::Memory;
+use c8common::Address;
+use clap::Parser;
+use std::error::Error;
+
+/// Prints every address at which two ROMs, or two memory images, differ
+#[derive(Parser, Debug)]
+struct Args {
+    left: String,
+    right: String,
+    /// Compare full 4096-byte memory images instead of ROMs
+    #[arg(long = "memory")]
+    memory: bool,
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let Args {
+        left,
+        right,
+        memory,
+    } = Args::parse();
+
+    let differences = if memory {
+        let left = Memory::from_file(left).map_err(|e| format!("{:?}", e))?;
+        let right = Memory::from_file(right).map_err(|e| format!("{:?}", e))?;
+        left.diff(&right)
+    } else {
+        let left = ROM::from_file(left).map_err(|e| format!("{:?}", e))?;
+        let right = ROM::from_file(right).map_err(|e| format!("{:?}", e))?;
+        left.diff(&right)
+            .into_iter()
+            .map(|(addr, l, r)| {
+                (
+                    Address::new(addr.as_u16() + Address::PROGRAM_START.as_u16()),
+                    l,
+                    r,
+                )
+            })
+            .collect()
+    };
+
+    for (addr, l, r) in &differences {
+        println!("{:03X}: {:02X} -> {:02X}", addr, l, r);
+    }
+    if !differences.is_empty() {
+        std::process::exit(1);
+    }
+    Ok(())
+}
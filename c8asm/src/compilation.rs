@@ -1,11 +1,14 @@
+use crate::instruction_sets::{Chip8InstructionSet, SuperChipInstructionSet, XoChipInstructionSet};
 use crate::parsing::{ExecutionItem, Label, LocalBinding, ReservedName, Value};
 use crate::tokenizing::Spanned;
 use c8common::asm::ROM;
-use c8common::{Address, Datum, GeneralRegister as VX, NUMBER_OF_ADDRESSES};
+use c8common::memory::Memory;
+use c8common::{Address, Datum, GeneralRegister as VX, Instruction, NUMBER_OF_ADDRESSES};
 pub use error::*;
-use log::info;
+use log::{info, warn};
 use miette::SourceSpan;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::str::FromStr;
 
 pub fn compile<B: InstructionBuilder>(
     items: Vec<Spanned<ExecutionItem>>,
@@ -13,14 +16,106 @@ pub fn compile<B: InstructionBuilder>(
     Assembler::with(items).assemble::<B>()
 }
 
+/// `compile` with the instruction set picked at runtime, see [`Target`]
+pub fn compile_for(
+    target: Target,
+    items: Vec<Spanned<ExecutionItem>>,
+) -> Result<ROM, CompilationError> {
+    let assembler = Assembler::with(items).with_target(target);
+    match target {
+        Target::Chip8 => assembler.assemble::<Chip8InstructionSet>(),
+        Target::SuperChip => assembler.assemble::<SuperChipInstructionSet>(),
+        Target::XoChip => assembler.assemble::<XoChipInstructionSet>(),
+    }
+}
+
+/// Compiles straight to a full memory image, with the font loaded
+pub fn compile_to_memory<B: InstructionBuilder>(
+    items: Vec<Spanned<ExecutionItem>>,
+) -> Result<Memory, CompilationError> {
+    Ok(compile::<B>(items)?.to_memory())
+}
+
+pub fn compile_with_debug<B: InstructionBuilder>(
+    items: Vec<Spanned<ExecutionItem>>,
+) -> Result<(ROM, DebugInfo), CompilationError> {
+    Assembler::with(items).assemble_with_debug::<B>()
+}
+
+/// Maps the address of every assembled instruction back to the source it came from
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct DebugInfo {
+    spans: BTreeMap<Address, SourceSpan>,
+    entry: Option<Address>,
+}
+
+impl DebugInfo {
+    /// The address named by `.entry`, if the program has one. A `.ch8` file has nowhere to keep
+    /// this, so it is only honoured by interpreters given the `DebugInfo`, such as `c8int`'s
+    /// `Chip8Interpreter::new_from_assembled`.
+    pub fn entry(&self) -> Option<Address> {
+        self.entry
+    }
+
+    /// The source of the instruction starting at `address`
+    pub fn span_at(&self, address: Address) -> Option<SourceSpan> {
+        self.spans.get(&address).copied()
+    }
+
+    /// The address of the instruction whose source contains `offset`
+    pub fn address_of(&self, offset: usize) -> Option<Address> {
+        self.spans
+            .iter()
+            .find(|(_, span)| (span.offset()..span.offset() + span.len()).contains(&offset))
+            .map(|(address, _)| *address)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Address, SourceSpan)> + '_ {
+        self.spans.iter().map(|(address, span)| (*address, *span))
+    }
+}
+
+/// The interpreter a program is assembled for
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub enum Target {
+    #[default]
+    Chip8,
+    SuperChip,
+    XoChip,
+}
+
+impl FromStr for Target {
+    type Err = UnknownTarget;
+
+    /// The names taken by `assemble --target`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "chip8" => Ok(Self::Chip8),
+            "schip" => Ok(Self::SuperChip),
+            "xochip" => Ok(Self::XoChip),
+            _ => Err(UnknownTarget {
+                name: s.to_string(),
+            }),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct Assembler {
     items: Vec<Spanned<ExecutionItem>>,
+    lints: bool,
+    target: Target,
+    strict: bool,
 }
 
 impl Assembler {
     pub fn new() -> Self {
-        Self { items: vec![] }
+        Self {
+            items: vec![],
+            lints: false,
+            target: Target::default(),
+            strict: false,
+        }
     }
 
     #[allow(clippy::needless_update)]
@@ -31,26 +126,64 @@ impl Assembler {
         }
     }
 
+    /// Opt in to static checks for common CHIP-8 mistakes
+    pub fn with_lints(mut self, lints: bool) -> Self {
+        self.lints = lints;
+        self
+    }
+
+    /// Some warnings only apply to some targets
+    pub fn with_target(mut self, target: Target) -> Self {
+        self.target = target;
+        self
+    }
+
+    /// In strict mode, `nop` (`0x0000`) is an error rather than an instruction, as it isn't part of
+    /// CHIP-8. Data bytes can still be zero.
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
     pub fn assemble<B: InstructionBuilder>(self) -> Result<ROM, CompilationError> {
-        let Self { items } = self;
+        let (rom, warnings) = self.assemble_with_warnings::<B>()?;
+        for warning in warnings {
+            warn!("{}", warning);
+        }
+        Ok(rom)
+    }
+
+    pub fn assemble_with_warnings<B: InstructionBuilder>(
+        self,
+    ) -> Result<(ROM, Vec<CompilationWarning>), CompilationError> {
+        let (rom, warnings, _) = self.assemble_all::<B>()?;
+        Ok((rom, warnings))
+    }
+
+    pub fn assemble_with_debug<B: InstructionBuilder>(
+        self,
+    ) -> Result<(ROM, DebugInfo), CompilationError> {
+        let (rom, warnings, debug) = self.assemble_all::<B>()?;
+        for warning in warnings {
+            warn!("{}", warning);
+        }
+        Ok((rom, debug))
+    }
+
+    /// Resolves constants and locals without emitting any bytes, leaving labels symbolic
+    pub fn to_program(self) -> Result<Vec<MappedItem>, CompilationError> {
+        Self::map_items(self.items)
+    }
+
+    fn map_items(items: Vec<Spanned<ExecutionItem>>) -> Result<Vec<MappedItem>, CompilationError> {
         let mut mapped_items: Vec<MappedItem> = vec![];
-        let mut constants: HashMap<String, ConcreteValue> = HashMap::new();
+        let constants = Self::resolve_constants(&items)?;
         let mut locals: HashMap<String, ConcreteValue> = HashMap::new();
+        let mut entry_at: Option<SourceSpan> = None;
 
         for Spanned { item, at } in items {
             match item {
-                ExecutionItem::Nothing => {}
-                ExecutionItem::DefineConstant { name, value } => {
-                    if constants
-                        .insert(
-                            name.clone(),
-                            ConcreteValue::create(value.spanned(at), &constants, &locals)?,
-                        )
-                        .is_some()
-                    {
-                        Err(ValueError::rebound_constant(name, at))?
-                    }
-                }
+                ExecutionItem::Nothing | ExecutionItem::DefineConstant { .. } => {}
                 ExecutionItem::BindLocal(bindings) => {
                     for LocalBinding { name, value } in bindings {
                         if locals
@@ -73,6 +206,13 @@ impl Assembler {
                     Label::Direct(name) => {
                         mapped_items.push(MappedItem::Label(name));
                     }
+                    Label::Entry(name) => {
+                        if let Some(first) = entry_at {
+                            Err(CompilationError::duplicate_entry(first, at))?
+                        }
+                        entry_at = Some(at);
+                        mapped_items.push(MappedItem::Entry(Spanned { item: name, at }));
+                    }
                     Label::AssertAddress(addr) => match addr {
                         Value::Numeric(i) => {
                             mapped_items.push(MappedItem::AssertAddress(Spanned { item: i, at }))
@@ -94,9 +234,105 @@ impl Assembler {
                 }
             }
         }
+        Ok(mapped_items)
+    }
+
+    /// Constants may be used before they are defined, like labels, so they are all resolved up
+    /// front, following references between them
+    fn resolve_constants(
+        items: &[Spanned<ExecutionItem>],
+    ) -> Result<HashMap<String, ConcreteValue>, ValueError> {
+        let mut definitions: HashMap<String, Spanned<Value>> = HashMap::new();
+        for Spanned { item, at } in items {
+            if let ExecutionItem::DefineConstant { name, value } = item {
+                if definitions
+                    .insert(name.clone(), value.clone().spanned(*at))
+                    .is_some()
+                {
+                    Err(ValueError::rebound_constant(name.clone(), *at))?
+                }
+            }
+        }
+        let mut constants = HashMap::new();
+        // In source order, so the error for a cycle always names its first definition
+        for item in items {
+            if let ExecutionItem::DefineConstant { name, .. } = &item.item {
+                Self::resolve_constant(name, &definitions, &mut constants, &mut vec![])?;
+            }
+        }
+        Ok(constants)
+    }
+
+    /// `resolving` holds the constants whose definitions led here, to catch cycles
+    fn resolve_constant(
+        name: &str,
+        definitions: &HashMap<String, Spanned<Value>>,
+        constants: &mut HashMap<String, ConcreteValue>,
+        resolving: &mut Vec<String>,
+    ) -> Result<ConcreteValue, ValueError> {
+        if let Some(value) = constants.get(name) {
+            return Ok(value.clone());
+        }
+        let Spanned { item, at } = definitions[name].clone();
+        if resolving.iter().any(|other| other == name) {
+            Err(ValueError::constant_cycle(name.to_string(), at))?
+        }
+        let value = match item {
+            Value::Constant(other) if definitions.contains_key(&other) => {
+                resolving.push(name.to_string());
+                let value = Self::resolve_constant(&other, definitions, constants, resolving)?;
+                resolving.pop();
+                value
+            }
+            other => ConcreteValue::create(other.spanned(at), constants, &HashMap::new())?,
+        };
+        constants.insert(name.to_string(), value.clone());
+        Ok(value)
+    }
+
+    /// Lays the program out from `base` rather than `0x200`, for assembling banks of a larger
+    /// program separately. Labels take addresses in the window, and the result is exactly `len`
+    /// bytes, zero-padded, so banks can be joined end to end.
+    pub fn assemble_window<B: InstructionBuilder>(
+        self,
+        base: Address,
+        len: usize,
+    ) -> Result<Vec<Datum>, CompilationError> {
+        let base = base.as_u16() as usize;
+        let (mut out, warnings, _) = self.layout::<B>(base, len.min(NUMBER_OF_ADDRESSES - base))?;
+        for warning in warnings {
+            warn!("{}", warning);
+        }
+        out.resize(len, Datum(0));
+        Ok(out)
+    }
 
-        let mut out = [Datum(0); NUMBER_OF_ADDRESSES - Address::PROGRAM_START_INDEX];
-        let mut counter = Address::PROGRAM_START_INDEX;
+    fn assemble_all<B: InstructionBuilder>(
+        self,
+    ) -> Result<(ROM, Vec<CompilationWarning>, DebugInfo), CompilationError> {
+        const CAPACITY: usize = NUMBER_OF_ADDRESSES - Address::PROGRAM_START_INDEX;
+        let (out, warnings, debug) = self.layout::<B>(Address::PROGRAM_START_INDEX, CAPACITY)?;
+        let mut data = [Datum(0); CAPACITY];
+        data[..out.len()].copy_from_slice(&out);
+        Ok((ROM::containing_program(data, out.len()), warnings, debug))
+    }
+
+    /// The bytes of the program placed at `base`, up to the last one written
+    fn layout<B: InstructionBuilder>(
+        self,
+        base: usize,
+        capacity: usize,
+    ) -> Result<(Vec<Datum>, Vec<CompilationWarning>, DebugInfo), CompilationError> {
+        let Self {
+            items,
+            lints,
+            target,
+            strict,
+        } = self;
+        let mapped_items = Self::map_items(items)?;
+
+        let mut out = vec![Datum(0); capacity];
+        let mut counter = base;
         let mut labels = HashMap::new();
         for mapped in mapped_items.iter() {
             match mapped {
@@ -120,58 +356,149 @@ impl Assembler {
                 MappedItem::Instruction { .. } => {
                     counter += 2;
                 }
+                MappedItem::Entry(_) => {}
             }
         }
-        let mut counter = Address::PROGRAM_START_INDEX;
+        // Checked before anything is placed, as addresses past the end of memory can't be built
+        if counter - base > capacity {
+            Err(CompilationError::does_not_fit(capacity))?
+        }
+        let mut counter = base;
+        let mut warnings = vec![];
+        let mut debug = DebugInfo::default();
+        let mut previous: Option<(Instruction, SourceSpan)> = None;
+        let mut last: Option<(Instruction, SourceSpan)> = None;
         for mapped in mapped_items.into_iter() {
             match mapped {
                 MappedItem::RawDatum(raw) => {
-                    out[counter - Address::PROGRAM_START_INDEX] = Datum(raw);
+                    out[counter - base] = Datum(raw);
                     counter += 1;
+                    previous = None;
                 }
                 MappedItem::Instruction {
                     opcode,
                     at,
                     arguments,
+                    argument_spans,
                 } => {
-                    let (high, low) = B::instruction(opcode, arguments, at, &labels)?;
+                    let (high, low) =
+                        B::instruction(opcode, arguments, at, counter as u16, &labels)
+                            .map_err(|error| error.with_argument_spans(&argument_spans))?;
                     // dbg!(format!("0x{:04X}", u16::from_be_bytes([high, low])));
-                    out[counter - Address::PROGRAM_START_INDEX] = Datum(high);
-                    counter += 1;
-                    out[counter - Address::PROGRAM_START_INDEX] = Datum(low);
-                    counter += 1;
+                    debug.spans.insert(Address::new(counter as u16), at);
+                    for datum in [high, low] {
+                        out[counter - base] = Datum(datum);
+                        counter += 1;
+                    }
+                    let current = Instruction::try_from_data((high, low).into())
+                        .ok()
+                        .map(|instruction| (instruction, at));
+                    let empty_sprite = matches!(
+                        current,
+                        Some((
+                            Instruction::DisplaySprite {
+                                number_of_bytes: 0,
+                                ..
+                            },
+                            _
+                        ))
+                    );
+                    if strict && matches!(current, Some((Instruction::Nop, _))) {
+                        return Err(CompilationError::strict_nop(at));
+                    }
+                    last = current;
+                    if empty_sprite && target == Target::Chip8 {
+                        warnings.push(CompilationWarning::empty_sprite(at));
+                    }
+                    if lints {
+                        if let (Some(previous), Some(current)) = (previous, current) {
+                            warnings.extend(Self::lint_clobbered_vf(previous, current));
+                        }
+                        previous = current;
+                    }
+                }
+                MappedItem::Entry(Spanned { item, at }) => {
+                    let address = labels
+                        .get(&item)
+                        .ok_or_else(|| CompilationError::unknown_entry(item, at))?;
+                    debug.entry = Some(Address::new(*address));
                 }
                 MappedItem::Label(_) | MappedItem::AssertAddress(_) => {}
             }
         }
 
-        Ok(ROM::containing(out))
+        if lints {
+            if let Some((instruction, at)) = last {
+                if !instruction.is_terminating() {
+                    warnings.push(CompilationWarning::falls_off_end(at));
+                }
+            }
+        }
+
+        out.truncate(counter - base);
+        Ok((out, warnings, debug))
+    }
+
+    /// A value written to `VF` is lost if the next instruction uses it as a flag without reading it first
+    fn lint_clobbered_vf(
+        (written, set): (Instruction, SourceSpan),
+        (next, clobbered): (Instruction, SourceSpan),
+    ) -> Option<CompilationWarning> {
+        let writes_vf = match written {
+            Instruction::LoadRegByte(x, _)
+            | Instruction::Add(x, _)
+            | Instruction::Random(x, _)
+            | Instruction::GetDelayTimer(x)
+            | Instruction::CopyRegToReg { x, .. }
+            | Instruction::Or { x, .. }
+            | Instruction::And { x, .. }
+            | Instruction::Xor { x, .. } => x == VX::VF,
+            Instruction::ReadMultiple(last) => last == VX::VF,
+            _ => false,
+        };
+        let reads = match next {
+            Instruction::AddReg { x, y }
+            | Instruction::Sub { x, y }
+            | Instruction::SubN { x, y }
+            | Instruction::Shr { x, y }
+            | Instruction::Shl { x, y }
+            | Instruction::DisplaySprite { x, y, .. } => [x, y],
+            _ => return None,
+        };
+        (writes_vf && !reads.contains(&VX::VF))
+            .then(|| CompilationWarning::clobbered_vf(set, clobbered))
     }
 
     fn instruction(
         opcode: String,
         at: SourceSpan,
-        arguments: Vec<Value>,
+        arguments: Vec<Spanned<Value>>,
         constants: &HashMap<String, ConcreteValue>,
         locals: &HashMap<String, ConcreteValue>,
     ) -> Result<MappedItem, ValueError> {
         Ok(MappedItem::Instruction {
             opcode,
             at,
+            argument_spans: arguments.iter().map(|value| value.at).collect(),
             arguments: arguments
                 .into_iter()
-                .map(|value| match value {
+                .map(|Spanned { item: value, at }| match value {
                     Value::Numeric(i) => Ok(ConcreteValue::Numeric(i)),
+                    Value::Negative(i) => Ok(ConcreteValue::Negative(i)),
                     Value::Constant(c) => constants
                         .get(&c)
                         .ok_or_else(|| ValueError::no_constant(c, at))
-                        .map(|i| i.clone()),
+                        .cloned(),
                     Value::Local(local) => locals
                         .get(&local)
                         .ok_or_else(|| ValueError::no_local(local, at))
-                        .map(|i| i.clone()),
+                        .cloned(),
                     Value::Name(name) => Ok(ConcreteValue::Reserved(name)),
-                    Value::Label(label) => Ok(ConcreteValue::name(label)),
+                    // A bare name bound with `.name` refers to the local, so registers can be aliased
+                    Value::Label(label) => Ok(locals
+                        .get(&label)
+                        .cloned()
+                        .unwrap_or_else(|| ConcreteValue::name(label))),
                 })
                 .collect::<Result<_, _>>()?,
         })
@@ -229,17 +556,22 @@ impl Assembler {
 pub enum MappedItem {
     Label(String),
     AssertAddress(Spanned<u16>),
+    /// The label named by `.entry`
+    Entry(Spanned<String>),
     RawDatum(u8),
     Instruction {
         opcode: String,
         at: SourceSpan,
         arguments: Vec<ConcreteValue>,
+        argument_spans: Vec<SourceSpan>,
     },
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum ConcreteValue {
     Numeric(u16),
+    /// A number written with a leading `-`, only meaningful to instructions taking offsets
+    Negative(u16),
     Register(VX),
     Label(String),
     Reserved(ReservedName),
@@ -253,6 +585,7 @@ impl ConcreteValue {
     ) -> Result<Self, ValueError> {
         match from.item {
             Value::Numeric(num) => Ok(Self::Numeric(num)),
+            Value::Negative(num) => Ok(Self::Negative(num)),
             Value::Constant(name) => Ok(constants
                 .get(&name)
                 .ok_or_else(|| ValueError::no_constant(name, from.at))?
@@ -306,10 +639,12 @@ impl ConcreteValue {
 }
 
 pub trait InstructionBuilder {
+    /// `address` is where this instruction will be placed
     fn instruction(
         opcode: String,
         arguments: Vec<ConcreteValue>,
         at: SourceSpan,
+        address: u16,
         labels: &HashMap<String, u16>,
     ) -> Result<(u8, u8), InstructionError>;
 }
@@ -330,12 +665,14 @@ mod error {
     #[derive(Debug, Error, Diagnostic)]
     pub enum InstructionError {
         #[error("Unknown instruction '{}'", .opcode)]
+        #[diagnostic(code(c8asm::compile::instruction::unknown_instruction))]
         UnknownInstruction {
             opcode: String,
             #[label("here")]
             at: SourceSpan,
         },
         #[error("Invalid arguments")]
+        #[diagnostic(code(c8asm::compile::instruction::invalid_arguments))]
         InvalidArguments {
             #[label("here")]
             at: SourceSpan,
@@ -343,11 +680,29 @@ mod error {
             reason: Option<String>,
         },
         #[error("Label '{}' is unknown", .label)]
+        #[diagnostic(code(c8asm::compile::instruction::missing_label))]
         MissingLabel {
             #[label("here")]
             at: SourceSpan,
             label: String,
         },
+        #[error("Invalid arguments")]
+        #[diagnostic(code(c8asm::compile::instruction::too_many_arguments))]
+        TooManyArguments {
+            #[label("here")]
+            at: SourceSpan,
+            expected: usize,
+            #[help]
+            reason: String,
+        },
+        #[error("Unexpected trailing tokens")]
+        #[diagnostic(code(c8asm::compile::instruction::trailing_tokens))]
+        TrailingTokens {
+            #[label("not part of the instruction")]
+            at: SourceSpan,
+            #[help]
+            reason: String,
+        },
     }
 
     impl InstructionError {
@@ -364,12 +719,26 @@ mod error {
 
         pub fn too_many_arguments(at: SourceSpan, expected: usize, got: usize) -> Self {
             assert!(expected < got);
-            Self::InvalidArguments {
+            Self::TooManyArguments {
                 at,
-                reason: Some(format!(
-                    "Too many arguments: Expected {}, got {}",
-                    expected, got
-                )),
+                expected,
+                reason: format!("Too many arguments: Expected {}, got {}", expected, got),
+            }
+        }
+
+        /// Points excess arguments at the tokens that weren't expected, rather than the whole line
+        pub(crate) fn with_argument_spans(self, spans: &[SourceSpan]) -> Self {
+            match self {
+                Self::TooManyArguments {
+                    expected, reason, ..
+                } if spans.len() > expected => {
+                    let (first, last) = (spans[expected], spans[spans.len() - 1]);
+                    Self::TrailingTokens {
+                        at: (first.offset(), last.offset() + last.len() - first.offset()).into(),
+                        reason,
+                    }
+                }
+                other => other,
             }
         }
 
@@ -397,6 +766,26 @@ mod error {
             }
         }
 
+        pub fn reserved_sys(at: SourceSpan, address: u16, instead: &str) -> Self {
+            Self::InvalidArguments {
+                at,
+                reason: Some(format!(
+                    "sys 0x{:03X} has the same encoding as `{}`, so would run as that instead",
+                    address, instead
+                )),
+            }
+        }
+
+        pub fn before_memory(at: SourceSpan, address: u16, offset: u16) -> Self {
+            Self::InvalidArguments {
+                at,
+                reason: Some(format!(
+                    "Going back 0x{:X} from 0x{:03X} is before the start of memory",
+                    offset, address
+                )),
+            }
+        }
+
         pub fn address(at: SourceSpan, address: u16) -> Result<u16, Self> {
             if address & 0xF000 != 0 {
                 Err(Self::address_too_large(at, address))
@@ -456,38 +845,129 @@ mod error {
         InstructionError(#[from] InstructionError),
 
         #[error("The label '{}' has been defined twice", .name)]
+        #[diagnostic(code(c8asm::compile::redefined_label))]
         RedefinedLabel { name: String },
 
         #[error("Expected an address of 0x{:03X}, found an address of 0x{:03X}", .expected, .got)]
+        #[diagnostic(code(c8asm::compile::assert_failed))]
         AssertFailed {
             expected: usize,
             got: usize,
             #[label("here")]
             at: SourceSpan,
         },
+
+        #[error("A program can only have one entry point")]
+        #[diagnostic(code(c8asm::compile::duplicate_entry))]
+        DuplicateEntry {
+            #[label("first set here")]
+            first: SourceSpan,
+            #[label("then again here")]
+            second: SourceSpan,
+        },
+        #[error("The entry point '{}' is not a label", .name)]
+        #[diagnostic(code(c8asm::compile::unknown_entry))]
+        UnknownEntry {
+            name: String,
+            #[label("here")]
+            at: SourceSpan,
+        },
+        #[error("The program doesn't fit in {} bytes", .capacity)]
+        #[diagnostic(code(c8asm::compile::does_not_fit))]
+        DoesNotFit { capacity: usize },
+        #[error("0x0000 is not a CHIP-8 instruction")]
+        #[diagnostic(
+            code(c8asm::compile::strict_nop),
+            help("strict mode rejects `nop`, use `.data 0, 0` if the zero bytes are intended")
+        )]
+        StrictNop {
+            #[label("here")]
+            at: SourceSpan,
+        },
+    }
+
+    #[derive(Debug, Error, Diagnostic)]
+    pub enum CompilationWarning {
+        #[error("VF is overwritten before it is used")]
+        #[diagnostic(
+            code(c8asm::compile::warning::clobbered_vf),
+            severity(Warning),
+            help("VF holds the flag result of this instruction, so use another register")
+        )]
+        ClobberedVF {
+            #[label("VF set here")]
+            set: SourceSpan,
+            #[label("then overwritten here")]
+            clobbered: SourceSpan,
+        },
+        #[error("A sprite of 0 bytes draws nothing")]
+        #[diagnostic(
+            code(c8asm::compile::warning::empty_sprite),
+            severity(Warning),
+            help("only SUPER-CHIP and XO-CHIP read `drw vx, vy, 0` as a 16x16 sprite")
+        )]
+        EmptySprite {
+            #[label("here")]
+            at: SourceSpan,
+        },
+        #[error("The program runs off the end after its last instruction")]
+        #[diagnostic(
+            code(c8asm::compile::warning::falls_off_end),
+            severity(Warning),
+            help("end the program with a jump, such as `end: jp end`")
+        )]
+        FallsOffEnd {
+            #[label("last instruction")]
+            at: SourceSpan,
+        },
+    }
+
+    impl CompilationWarning {
+        pub(super) fn clobbered_vf(set: SourceSpan, clobbered: SourceSpan) -> Self {
+            Self::ClobberedVF { set, clobbered }
+        }
+
+        pub(super) fn empty_sprite(at: SourceSpan) -> Self {
+            Self::EmptySprite { at }
+        }
+
+        pub(super) fn falls_off_end(at: SourceSpan) -> Self {
+            Self::FallsOffEnd { at }
+        }
     }
 
     #[derive(Debug, Error, Diagnostic)]
     pub enum ValueError {
         #[error("Constant '{}' cannot be rebound", .name)]
+        #[diagnostic(code(c8asm::compile::value::rebound_constant))]
         ReboundConstant {
             name: String,
             #[label("here")]
             at: SourceSpan,
         },
         #[error("No constant by the name '{}'", .name)]
+        #[diagnostic(code(c8asm::compile::value::no_constant))]
         NoConstant {
             name: String,
             #[label("here")]
             at: SourceSpan,
         },
+        #[error("Constant '{}' is defined in terms of itself", .name)]
+        #[diagnostic(code(c8asm::compile::value::constant_cycle))]
+        ConstantCycle {
+            name: String,
+            #[label("here")]
+            at: SourceSpan,
+        },
         #[error("No local by the name '{}'", .name)]
+        #[diagnostic(code(c8asm::compile::value::no_local))]
         NoLocal {
             name: String,
             #[label("here")]
             at: SourceSpan,
         },
         #[error("Asserts must use a numeric address")]
+        #[diagnostic(code(c8asm::compile::value::assert_non_numeric))]
         AssertNonNumeric {
             #[label("here")]
             at: SourceSpan,
@@ -502,6 +982,22 @@ mod error {
         pub(super) fn assert_failed(expected: usize, got: usize, at: SourceSpan) -> Self {
             Self::AssertFailed { expected, got, at }
         }
+
+        pub(super) fn duplicate_entry(first: SourceSpan, second: SourceSpan) -> Self {
+            Self::DuplicateEntry { first, second }
+        }
+
+        pub(super) fn unknown_entry(name: String, at: SourceSpan) -> Self {
+            Self::UnknownEntry { name, at }
+        }
+
+        pub(super) fn does_not_fit(capacity: usize) -> Self {
+            Self::DoesNotFit { capacity }
+        }
+
+        pub(super) fn strict_nop(at: SourceSpan) -> Self {
+            Self::StrictNop { at }
+        }
     }
 
     impl ValueError {
@@ -517,8 +1013,241 @@ mod error {
             Self::ReboundConstant { name, at }
         }
 
+        pub(super) fn constant_cycle(name: String, at: SourceSpan) -> Self {
+            Self::ConstantCycle { name, at }
+        }
+
         pub(super) fn assert_non_numeric(at: SourceSpan) -> Self {
             Self::AssertNonNumeric { at }
         }
     }
+
+    #[derive(Debug, Error, Diagnostic)]
+    #[error("Unknown target '{}'", .name)]
+    #[diagnostic(
+        code(c8asm::compile::unknown_target),
+        help("the targets are chip8, schip and xochip")
+    )]
+    pub struct UnknownTarget {
+        pub(super) name: String,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instruction_sets::Chip8InstructionSet;
+    use crate::parsing::parse;
+    use crate::tokenizing::tokenize;
+    use c8common::memory::FONT_START_ADDR;
+
+    fn warnings(source: &str) -> Vec<CompilationWarning> {
+        Assembler::with(parse(tokenize(source).unwrap()).unwrap())
+            .with_lints(true)
+            .assemble_with_warnings::<Chip8InstructionSet>()
+            .unwrap()
+            .1
+    }
+
+    #[test]
+    fn test_clobbered_vf_lint() {
+        // Each program ends in a jump, so that it doesn't fall off the end
+        let clobbered = warnings("ld vf, 1\nadd v0, v1\njp 0x200\n");
+        assert!(matches!(
+            clobbered[..],
+            [CompilationWarning::ClobberedVF { .. }]
+        ));
+        assert!(warnings("ld vf, 1\nadd v0, vf\njp 0x200\n").is_empty());
+        assert!(warnings("ld vf, 1\ncls\nadd v0, v1\njp 0x200\n").is_empty());
+    }
+
+    #[test]
+    fn test_compile_to_memory_has_font() {
+        let memory =
+            compile_to_memory::<Chip8InstructionSet>(parse(tokenize("cls\n").unwrap()).unwrap())
+                .unwrap();
+        let font = Address::new(FONT_START_ADDR as u16);
+        assert_eq!(
+            memory.substring(font, 5),
+            [0xF0, 0x90, 0x90, 0x90, 0xF0].map(Datum)
+        );
+        assert_eq!(memory[Address::PROGRAM_START], Datum(0x00));
+        assert_eq!(memory[Address::new(0x201)], Datum(0xE0));
+    }
+
+    #[test]
+    fn test_debug_info_spans() {
+        let source = "cls\nloop:\n.data 0x12\n  jp loop\n";
+        let (rom, debug) =
+            compile_with_debug::<Chip8InstructionSet>(parse(tokenize(source).unwrap()).unwrap())
+                .unwrap();
+
+        assert_eq!(rom[Address::new(3)], Datum(0x12));
+        let jump = debug.span_at(Address::new(0x203)).unwrap();
+        assert_eq!(
+            &source[jump.offset()..jump.offset() + jump.len()],
+            "jp loop"
+        );
+        assert_eq!(debug.span_at(Address::new(0x202)), None);
+        assert_eq!(debug.address_of(jump.offset()), Some(Address::new(0x203)));
+        assert_eq!(debug.iter().count(), 2);
+    }
+
+    #[test]
+    fn test_entry() {
+        let compile = |source: &str| {
+            compile_with_debug::<Chip8InstructionSet>(parse(tokenize(source).unwrap()).unwrap())
+        };
+        let (_, debug) = compile(".entry main\nsub:\nret\nmain:\ncall sub\n").unwrap();
+        assert_eq!(debug.entry(), Some(Address::new(0x202)));
+        let (_, debug) = compile("cls\n").unwrap();
+        assert_eq!(debug.entry(), None);
+
+        assert!(matches!(
+            compile(".entry a\na:\n.entry a\n"),
+            Err(CompilationError::DuplicateEntry { .. })
+        ));
+        assert!(matches!(
+            compile(".entry nowhere\ncls\n"),
+            Err(CompilationError::UnknownEntry { .. })
+        ));
+    }
+
+    #[test]
+    fn test_to_program() {
+        let program =
+            Assembler::with(parse(tokenize("$n 3\nstart:\nld v0, $n\n").unwrap()).unwrap())
+                .to_program()
+                .unwrap();
+
+        assert!(matches!(&program[..], [MappedItem::Label(label), _] if label == "start"));
+        let MappedItem::Instruction {
+            opcode, arguments, ..
+        } = &program[1]
+        else {
+            panic!("expected an instruction, got {:?}", program[1]);
+        };
+        assert_eq!(opcode, "ld");
+        assert_eq!(
+            arguments,
+            &[ConcreteValue::Register(VX::V0), ConcreteValue::Numeric(3)]
+        );
+    }
+
+    #[test]
+    fn test_program_len_counts_trailing_zeroes() {
+        let rom = compile::<Chip8InstructionSet>(
+            parse(tokenize("cls\nld v0, 1\n.data 0, 0\nnop\n").unwrap()).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(rom.program_len(), 8);
+        assert_eq!(rom.to_hex_dump(), "200: 00 E0 60 01 00 00 00 00\n");
+    }
+
+    #[test]
+    fn test_empty_sprite_warning() {
+        let assemble = |target| {
+            Assembler::with(parse(tokenize("drw v0, v1, 0\ndrw v0, v1, 5\n").unwrap()).unwrap())
+                .with_target(target)
+                .assemble_with_warnings::<Chip8InstructionSet>()
+                .unwrap()
+                .1
+        };
+        match &assemble(Target::Chip8)[..] {
+            [CompilationWarning::EmptySprite { at }] => assert_eq!(at.offset(), 0),
+            other => panic!("expected one empty sprite warning, got {:?}", other),
+        }
+        assert!(assemble(Target::SuperChip).is_empty());
+    }
+
+    #[test]
+    fn test_assemble_window() {
+        let assemble = |source: &str| {
+            Assembler::with(parse(tokenize(source).unwrap()).unwrap())
+                .assemble_window::<Chip8InstructionSet>(Address::new(0x300), 256)
+        };
+        let bank = assemble("cls\nloop:\njp loop\n.assert_addr 0x304\n").unwrap();
+        assert_eq!(bank.len(), 256);
+        assert_eq!(bank[..4], [0x00, 0xE0, 0x13, 0x02].map(Datum));
+        assert!(bank[4..].iter().all(|datum| *datum == Datum(0)));
+
+        match assemble(&"cls\n".repeat(129)) {
+            Err(CompilationError::DoesNotFit { capacity }) => assert_eq!(capacity, 256),
+            other => panic!("expected the bank to overflow, got {:?}", other),
+        }
+        let last_bank = Assembler::with(parse(tokenize(&"cls\n".repeat(129)).unwrap()).unwrap())
+            .assemble_window::<Chip8InstructionSet>(Address::new(0xF00), 256);
+        assert!(matches!(
+            last_bank,
+            Err(CompilationError::DoesNotFit { capacity: 256 })
+        ));
+    }
+
+    #[test]
+    fn test_program_too_large() {
+        let assemble = |source: &str| {
+            Assembler::with(parse(tokenize(source).unwrap()).unwrap())
+                .assemble::<Chip8InstructionSet>()
+        };
+        let rom = assemble(&"cls\n".repeat(1792)).unwrap();
+        assert_eq!(rom.program_len(), 0xE00);
+        match assemble(&"cls\n".repeat(1793)) {
+            Err(CompilationError::DoesNotFit { capacity }) => assert_eq!(capacity, 0xE00),
+            other => panic!("expected the program to overflow, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_strict_rejects_nop() {
+        let assemble = |strict| {
+            Assembler::with(parse(tokenize("cls\nnop\n.data 0, 0\n").unwrap()).unwrap())
+                .with_strict(strict)
+                .assemble::<Chip8InstructionSet>()
+        };
+        match assemble(true) {
+            Err(CompilationError::StrictNop { at }) => assert_eq!(at.offset(), 4),
+            other => panic!("expected nop to be rejected, got {:?}", other),
+        }
+        assert!(assemble(false).is_ok());
+        assert!(
+            Assembler::with(parse(tokenize("cls\n.data 0, 0\n").unwrap()).unwrap())
+                .with_strict(true)
+                .assemble::<Chip8InstructionSet>()
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_forward_constants() {
+        let assemble = |source: &str| {
+            Assembler::with(parse(tokenize(source).unwrap()).unwrap())
+                .assemble::<Chip8InstructionSet>()
+        };
+        let rom = assemble("$a $b\nld v0, $a\n$b $c\n$c 5\n").unwrap();
+        assert_eq!(
+            [rom[Address::new(0)], rom[Address::new(1)]],
+            [Datum(0x60), Datum(5)]
+        );
+
+        match assemble("$a $b\n$b $c\n$c $a\ncls\n") {
+            Err(CompilationError::ValueError(ValueError::ConstantCycle { .. })) => {}
+            other => panic!("expected a cycle, got {:?}", other),
+        }
+        match assemble("$a $nowhere\ncls\n") {
+            Err(CompilationError::ValueError(ValueError::NoConstant { name, .. })) => {
+                assert_eq!(name, "nowhere")
+            }
+            other => panic!("expected a missing constant, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_falls_off_end_lint() {
+        match &warnings("cls\nadd v0, 1\n")[..] {
+            [CompilationWarning::FallsOffEnd { at }] => assert_eq!(at.offset(), 4),
+            other => panic!("expected the program to fall off the end, got {:?}", other),
+        }
+        assert!(warnings("end:\njp end\n.data 1, 2\n").is_empty());
+        assert!(warnings("add v0, 1\nret\n").is_empty());
+    }
 }
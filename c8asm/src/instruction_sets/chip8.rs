@@ -6,18 +6,69 @@ use std::collections::HashMap;
 
 pub struct Chip8InstructionSet;
 
+impl Chip8InstructionSet {
+    /// Every form of every instruction accepted, as `(mnemonic, arguments)`
+    /// `addr` may be a number or a label, and `reg` is any of `v0`..`vf`
+    pub const MNEMONICS: &'static [(&'static str, &'static str)] = &[
+        ("nop", ""),
+        ("sys", "addr"),
+        ("cls", ""),
+        ("ret", ""),
+        ("jp", "addr"),
+        ("jp", "v0, addr"),
+        ("rel", "addr"),
+        ("rel", "offset"),
+        ("call", "addr"),
+        ("se", "reg, byte"),
+        ("se", "reg, reg"),
+        ("sne", "reg, byte"),
+        ("sne", "reg, reg"),
+        ("ld", "reg, byte"),
+        ("ld", "reg, reg"),
+        ("ld", "i, addr"),
+        ("ld", "reg, dt"),
+        ("ld", "reg, k"),
+        ("ld", "dt, reg"),
+        ("ld", "st, reg"),
+        ("ld", "f, reg"),
+        ("ld", "b, reg"),
+        ("ld", "i, reg"),
+        ("ld", "reg, i"),
+        ("add", "reg, byte"),
+        ("add", "reg, reg"),
+        ("add", "i, reg"),
+        ("or", "reg, reg"),
+        ("and", "reg, reg"),
+        ("xor", "reg, reg"),
+        ("sub", "reg, reg"),
+        ("subn", "reg, reg"),
+        ("shr", "reg"),
+        ("shr", "reg, reg"),
+        ("shl", "reg"),
+        ("shl", "reg, reg"),
+        ("rnd", "reg, byte"),
+        ("drw", "reg, reg, nibble"),
+        ("skp", "reg"),
+        ("sknp", "reg"),
+    ];
+}
+
 impl InstructionBuilder for Chip8InstructionSet {
     fn instruction(
         opcode: String,
         arguments: Vec<ConcreteValue>,
         at: SourceSpan,
+        address: u16,
         labels: &HashMap<String, u16>,
     ) -> Result<(u8, u8), InstructionError> {
         match &opcode[..] {
+            "nop" => Self::no_args(at, arguments, (0x00, 0x00)),
             "cls" => Self::no_args(at, arguments, (0x00, 0xE0)),
             "ret" => Self::no_args(at, arguments, (0x00, 0xEE)),
             "jp" => Self::jump(at, arguments, labels),
-            "call" => Self::call(at, arguments, labels),
+            "rel" => Self::relative_jump(at, address, arguments, labels),
+            "sys" => Self::sys(at, arguments, labels),
+            "call" => Self::call(0x20, at, arguments, labels),
             "se" => Self::skip(false, at, arguments),
             "sne" => Self::skip(true, at, arguments),
             "ld" => Self::load(at, arguments, labels),
@@ -26,8 +77,9 @@ impl InstructionBuilder for Chip8InstructionSet {
             op @ ("or" | "and" | "xor" | "sub" | "subn") => {
                 Self::operation(op, at, Self::two_args(at, arguments)?)
             }
-            // ALU
-            // Rand
+            "shr" => Self::shift(0x06, at, arguments),
+            "shl" => Self::shift(0x0E, at, arguments),
+            "rnd" => Self::random(at, arguments),
             "drw" => Self::draw_sprite(at, arguments),
             "skp" => Self::skip_key(false, at, arguments),
             "sknp" => Self::skip_key(true, at, arguments),
@@ -50,7 +102,7 @@ fn register_to_byte(reg: VX) -> u8 {
 }
 
 impl Chip8InstructionSet {
-    fn no_args(
+    pub(super) fn no_args(
         at: SourceSpan,
         arguments: Vec<ConcreteValue>,
         correct: (u8, u8),
@@ -58,7 +110,7 @@ impl Chip8InstructionSet {
         if arguments.is_empty() {
             Ok(correct)
         } else {
-            Err(InstructionError::too_many_arguments(at, 1, arguments.len()))
+            Err(InstructionError::too_many_arguments(at, 0, arguments.len()))
         }
     }
 
@@ -81,14 +133,14 @@ impl Chip8InstructionSet {
         }
     }
 
-    fn one_arg(
+    pub(super) fn one_arg(
         at: SourceSpan,
         mut arguments: Vec<ConcreteValue>,
     ) -> Result<ConcreteValue, InstructionError> {
         if let [_] = arguments[..] {
             let first = arguments.pop().expect("known by match");
             Ok(first)
-        } else if arguments.len() > 2 {
+        } else if arguments.len() > 1 {
             Err(InstructionError::too_many_arguments(at, 1, arguments.len()))
         } else {
             Err(InstructionError::not_enough_arguments(
@@ -170,11 +222,48 @@ impl Chip8InstructionSet {
     fn add(at: SourceSpan, arguments: Vec<ConcreteValue>) -> Result<(u8, u8), InstructionError> {
         use ConcreteValue::*;
         let (first, last) = Self::two_args(at, arguments)?;
-        if let (Register(reg), Numeric(num)) = (&first, &last) {
-            let byte = byte(at, *num)?;
-            Ok((0x70 | register_to_byte(*reg), byte))
+        match (&first, &last) {
+            (Register(reg), Numeric(num)) => {
+                let byte = byte(at, *num)?;
+                Ok((0x70 | register_to_byte(*reg), byte))
+            }
+            (Reserved(ReservedName::I), Register(reg)) => Ok((0xF0 | register_to_byte(*reg), 0x1E)),
+            _ => Self::operation("add", at, (first, last)),
+        }
+    }
+
+    /// `shr vx` or `shr vx, vy`, where `vy` only matters to interpreters with the shift quirk
+    fn shift(
+        low: u8,
+        at: SourceSpan,
+        arguments: Vec<ConcreteValue>,
+    ) -> Result<(u8, u8), InstructionError> {
+        let (vx, vy) = if arguments.len() == 1 {
+            (
+                Self::one_arg(at, arguments)?,
+                ConcreteValue::Register(VX::V0),
+            )
         } else {
-            Self::operation("add", at, (first, last))
+            Self::two_args(at, arguments)?
+        };
+        let vx = InstructionError::expects_register(at, vx)?;
+        let vy = InstructionError::expects_register(at, vy)?;
+        Ok((
+            0x80 | register_to_byte(vx),
+            (register_to_byte(vy) << 4) | low,
+        ))
+    }
+
+    fn random(at: SourceSpan, arguments: Vec<ConcreteValue>) -> Result<(u8, u8), InstructionError> {
+        match Self::two_args(at, arguments)? {
+            (ConcreteValue::Register(reg), ConcreteValue::Numeric(num)) => {
+                Ok((0xC0 | register_to_byte(reg), byte(at, num)?))
+            }
+            _ => Err(InstructionError::invalid_type(
+                at,
+                "a register+byte pair",
+                "something else",
+            )),
         }
     }
 
@@ -221,7 +310,11 @@ impl Chip8InstructionSet {
         use ConcreteValue::*;
         use ReservedName::*;
         match arguments[..] {
-            [_] => Err(InstructionError::not_enough_arguments(at, 2, 1)),
+            [] | [_] => Err(InstructionError::not_enough_arguments(
+                at,
+                2,
+                arguments.len(),
+            )),
             [_, _] => {
                 let second = arguments
                     .pop()
@@ -260,7 +353,9 @@ impl Chip8InstructionSet {
         }
     }
 
+    /// `opcode` is `0x20` for `call`, or `0x00` for a `sys` machine code call
     fn call(
+        opcode: u8,
         at: SourceSpan,
         mut arguments: Vec<ConcreteValue>,
         labels: &HashMap<String, u16>,
@@ -276,7 +371,22 @@ impl Chip8InstructionSet {
         }?;
         let (high, low) = split_raw(target);
         assert!(high <= 0xF);
-        Ok((0x20 | high, low))
+        Ok((opcode | high, low))
+    }
+
+    /// `0nnn`, except for the addresses whose encodings are `nop`, `cls` and `ret`
+    fn sys(
+        at: SourceSpan,
+        arguments: Vec<ConcreteValue>,
+        labels: &HashMap<String, u16>,
+    ) -> Result<(u8, u8), InstructionError> {
+        let (high, low) = Self::call(0x00, at, arguments, labels)?;
+        match u16::from_be_bytes([high, low]) {
+            0x000 => Err(InstructionError::reserved_sys(at, 0x000, "nop")),
+            0x0E0 => Err(InstructionError::reserved_sys(at, 0x0E0, "cls")),
+            0x0EE => Err(InstructionError::reserved_sys(at, 0x0EE, "ret")),
+            _ => Ok((high, low)),
+        }
     }
 
     fn jump(
@@ -313,10 +423,34 @@ impl Chip8InstructionSet {
                     Ok((0xB0 | high, low))
                 }
             }
-            _ => Err(InstructionError::too_many_arguments(at, 1, arguments.len())),
+            _ => Err(InstructionError::too_many_arguments(at, 2, arguments.len())),
         }
     }
 
+    /// `rel n` to jump `n` bytes from the start of this instruction (so `rel 0` loops on itself),
+    /// or `rel -n` to jump back before it.
+    /// `rel label` targets the label itself, just like `jp label`.
+    /// Either way this assembles to a normal absolute `1nnn`
+    fn relative_jump(
+        at: SourceSpan,
+        address: u16,
+        arguments: Vec<ConcreteValue>,
+        labels: &HashMap<String, u16>,
+    ) -> Result<(u8, u8), InstructionError> {
+        let target = match Self::one_arg(at, arguments)? {
+            ConcreteValue::Numeric(offset) => {
+                let target = address as u32 + offset as u32;
+                InstructionError::address(at, target.try_into().unwrap_or(u16::MAX))?
+            }
+            ConcreteValue::Negative(offset) => address
+                .checked_sub(offset)
+                .ok_or_else(|| InstructionError::before_memory(at, address, offset))?,
+            other => Self::get_jump_target(at, other, labels)?,
+        };
+        let (high, low) = split_raw(target);
+        Ok((0x10 | high, low))
+    }
+
     fn get_jump_target(
         at: SourceSpan,
         only: ConcreteValue,
@@ -337,7 +471,70 @@ impl Chip8InstructionSet {
                 "a valid jump address",
                 "a reserved name",
             ))?,
+            ConcreteValue::Negative(_) => Err(InstructionError::invalid_type(
+                at,
+                "a valid jump address",
+                "a negative number",
+            ))?,
         };
         InstructionError::address(at, target)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn forms(mnemonic: &str) -> Vec<&'static str> {
+        Chip8InstructionSet::MNEMONICS
+            .iter()
+            .filter(|(m, _)| *m == mnemonic)
+            .map(|(_, arguments)| *arguments)
+            .collect()
+    }
+
+    #[test]
+    fn test_mnemonic_table() {
+        assert_eq!(forms("drw"), ["reg, reg, nibble"]);
+        assert_eq!(forms("ld").len(), 11);
+        assert!(forms("ld")
+            .iter()
+            .all(|arguments| arguments.split(", ").count() == 2));
+
+        for (mnemonic, _) in Chip8InstructionSet::MNEMONICS {
+            let result = Chip8InstructionSet::instruction(
+                mnemonic.to_string(),
+                vec![],
+                (0, 0).into(),
+                0x200,
+                &HashMap::new(),
+            );
+            assert!(
+                !matches!(result, Err(InstructionError::UnknownInstruction { .. })),
+                "{} is listed but not accepted",
+                mnemonic
+            );
+        }
+    }
+
+    #[test]
+    fn test_sys_rejects_other_instructions() {
+        let sys = |address| {
+            Chip8InstructionSet::instruction(
+                "sys".to_string(),
+                vec![ConcreteValue::Numeric(address)],
+                (0, 0).into(),
+                0x200,
+                &HashMap::new(),
+            )
+        };
+        assert_eq!(sys(0x123).unwrap(), (0x01, 0x23));
+        for address in [0x000, 0x0E0, 0x0EE] {
+            assert!(
+                matches!(sys(address), Err(InstructionError::InvalidArguments { .. })),
+                "sys 0x{:03X} is accepted",
+                address
+            );
+        }
+    }
+}
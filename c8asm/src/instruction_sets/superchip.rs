@@ -0,0 +1,56 @@
+use super::Chip8InstructionSet;
+use crate::compilation::{ConcreteValue, InstructionBuilder, InstructionError};
+use miette::SourceSpan;
+use std::collections::HashMap;
+
+/// CHIP-8, plus the SUPER-CHIP 1.1 scrolling and display mode instructions
+pub struct SuperChipInstructionSet;
+
+impl SuperChipInstructionSet {
+    /// The forms added on top of [`Chip8InstructionSet::MNEMONICS`]
+    pub const MNEMONICS: &'static [(&'static str, &'static str)] = &[
+        ("scd", "nibble"),
+        ("scr", ""),
+        ("scl", ""),
+        ("exit", ""),
+        ("low", ""),
+        ("high", ""),
+    ];
+
+    fn scroll_down(
+        at: SourceSpan,
+        arguments: Vec<ConcreteValue>,
+    ) -> Result<(u8, u8), InstructionError> {
+        match Chip8InstructionSet::one_arg(at, arguments)? {
+            ConcreteValue::Numeric(rows) => {
+                Ok((0x00, 0xC0 | InstructionError::expects_nibble(at, rows)?))
+            }
+            _ => Err(InstructionError::invalid_type(
+                at,
+                "a number",
+                "something else",
+            )),
+        }
+    }
+}
+
+impl InstructionBuilder for SuperChipInstructionSet {
+    fn instruction(
+        opcode: String,
+        arguments: Vec<ConcreteValue>,
+        at: SourceSpan,
+        address: u16,
+        labels: &HashMap<String, u16>,
+    ) -> Result<(u8, u8), InstructionError> {
+        let no_args = Chip8InstructionSet::no_args;
+        match &opcode[..] {
+            "scd" => Self::scroll_down(at, arguments),
+            "scr" => no_args(at, arguments, (0x00, 0xFB)),
+            "scl" => no_args(at, arguments, (0x00, 0xFC)),
+            "exit" => no_args(at, arguments, (0x00, 0xFD)),
+            "low" => no_args(at, arguments, (0x00, 0xFE)),
+            "high" => no_args(at, arguments, (0x00, 0xFF)),
+            _ => Chip8InstructionSet::instruction(opcode, arguments, at, address, labels),
+        }
+    }
+}
@@ -0,0 +1,43 @@
+use super::{Chip8InstructionSet, SuperChipInstructionSet};
+use crate::compilation::{ConcreteValue, InstructionBuilder, InstructionError};
+use miette::SourceSpan;
+use std::collections::HashMap;
+
+/// SUPER-CHIP, plus the XO-CHIP plane selection
+pub struct XoChipInstructionSet;
+
+impl XoChipInstructionSet {
+    /// The forms added on top of [`SuperChipInstructionSet::MNEMONICS`]
+    pub const MNEMONICS: &'static [(&'static str, &'static str)] = &[("plane", "nibble")];
+
+    fn select_planes(
+        at: SourceSpan,
+        arguments: Vec<ConcreteValue>,
+    ) -> Result<(u8, u8), InstructionError> {
+        match Chip8InstructionSet::one_arg(at, arguments)? {
+            ConcreteValue::Numeric(mask) => {
+                Ok((0xF0 | InstructionError::expects_nibble(at, mask)?, 0x01))
+            }
+            _ => Err(InstructionError::invalid_type(
+                at,
+                "a number",
+                "something else",
+            )),
+        }
+    }
+}
+
+impl InstructionBuilder for XoChipInstructionSet {
+    fn instruction(
+        opcode: String,
+        arguments: Vec<ConcreteValue>,
+        at: SourceSpan,
+        address: u16,
+        labels: &HashMap<String, u16>,
+    ) -> Result<(u8, u8), InstructionError> {
+        match &opcode[..] {
+            "plane" => Self::select_planes(at, arguments),
+            _ => SuperChipInstructionSet::instruction(opcode, arguments, at, address, labels),
+        }
+    }
+}
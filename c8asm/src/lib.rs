@@ -1,8 +1,427 @@
+pub mod analysis;
 pub mod compilation;
 pub mod parsing;
 pub mod tokenizing;
 
 pub mod instruction_sets {
     mod chip8;
+    mod superchip;
+    mod xochip;
     pub use chip8::Chip8InstructionSet;
+    pub use superchip::SuperChipInstructionSet;
+    pub use xochip::XoChipInstructionSet;
+}
+
+use c8common::asm::ROM;
+use compilation::{compile_for, Target};
+pub use error::AssembleError;
+use parsing::{parse, parse_relative_to};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use tokenizing::tokenize;
+
+/// The path standing for stdin or stdout on the command line
+pub const STDIO_PATH: &str = "-";
+
+/// Runs the whole pipeline over some CHIP-8 assembly
+/// The returned error carries the source, so it can be reported directly with miette
+pub fn assemble_str(source: &str) -> Result<ROM, AssembleError> {
+    assemble_with(source, None, Target::Chip8)
+}
+
+/// Like `assemble_str`, with `.incbin` paths taken relative to `base_dir`, usually the
+/// directory the source was read from
+pub fn assemble_str_relative_to(
+    source: &str,
+    base_dir: impl AsRef<Path>,
+) -> Result<ROM, AssembleError> {
+    assemble_with(source, Some(base_dir.as_ref()), Target::Chip8)
+}
+
+/// Reads the source at `path`, or all of `stdin` if the path is [`STDIO_PATH`], along with the
+/// directory `.incbin` paths should be taken relative to. For stdin that is the working
+/// directory.
+pub fn read_source(path: &str, stdin: impl Read) -> Result<(String, PathBuf), std::io::Error> {
+    if path == STDIO_PATH {
+        return Ok((std::io::read_to_string(stdin)?, PathBuf::from(".")));
+    }
+    let base_dir = Path::new(path).parent().unwrap_or(Path::new("."));
+    Ok((std::fs::read_to_string(path)?, base_dir.to_path_buf()))
+}
+
+/// Checks an assembled ROM against a reference build, one line per mismatch, so regressions in
+/// the assembler's output can be caught. Offsets are from the start of the file. Empty if they
+/// match.
+///
+/// Only the bytes are compared: a saved ROM is always padded out to the end of memory, so the
+/// reference's length says nothing about the program in it, and a program that is longer or
+/// shorter differs in its bytes anyway.
+pub fn reference_mismatches(rom: &ROM, reference: &ROM) -> Vec<String> {
+    rom.diff(reference)
+        .into_iter()
+        .map(|(offset, got, expected)| {
+            format!(
+                "0x{:03X}: expected {:02X}, got {:02X}",
+                offset.as_u16(),
+                expected.0,
+                got.0
+            )
+        })
+        .collect()
+}
+
+/// The general form of `assemble_str`: `.incbin` paths are relative to `base_dir` if there is
+/// one, and only the instructions of `target` are accepted
+pub fn assemble_with(
+    source: &str,
+    base_dir: Option<&Path>,
+    target: Target,
+) -> Result<ROM, AssembleError> {
+    let tokens = tokenize(source).map_err(|error| AssembleError::Tokenize {
+        error,
+        source_code: source.to_string(),
+    })?;
+    let items = match base_dir {
+        Some(base_dir) => parse_relative_to(tokens, base_dir),
+        None => parse(tokens),
+    };
+    let items = items.map_err(|error| AssembleError::Parse {
+        error,
+        source_code: source.to_string(),
+    })?;
+    compile_for(target, items).map_err(|error| AssembleError::Compile {
+        error,
+        source_code: source.to_string(),
+    })
+}
+
+mod error {
+    use crate::compilation::CompilationError;
+    use crate::parsing::ConversionError;
+    use crate::tokenizing::TokenizingError;
+    use miette::Diagnostic;
+    use thiserror::Error;
+
+    #[derive(Debug, Error, Diagnostic)]
+    pub enum AssembleError {
+        #[error("{}", .error)]
+        #[diagnostic(forward(error))]
+        Tokenize {
+            error: TokenizingError,
+            #[source_code]
+            source_code: String,
+        },
+        #[error("{}", .error)]
+        #[diagnostic(forward(error))]
+        Parse {
+            error: ConversionError,
+            #[source_code]
+            source_code: String,
+        },
+        #[error("{}", .error)]
+        #[diagnostic(forward(error))]
+        Compile {
+            error: CompilationError,
+            #[source_code]
+            source_code: String,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compilation::{CompilationError, InstructionError};
+    use c8common::{Address, GeneralRegister, Instruction};
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use std::collections::HashSet;
+    use std::mem::discriminant;
+
+    const VARIANTS: usize = 37;
+
+    /// Builds the `variant`th kind of instruction, with random operands
+    fn arbitrary_instruction(rng: &mut StdRng, variant: usize) -> Instruction {
+        use Instruction::*;
+        let x = GeneralRegister::from_byte(rng.gen_range(0..16));
+        let y = GeneralRegister::from_byte(rng.gen_range(0..16));
+        let byte = rng.gen();
+        let addr = Address::new(rng.gen_range(0..=Address::MAX.as_u16()));
+        match variant {
+            0 => Nop,
+            1 => ClearScreen,
+            2 => Return,
+            3 => Jump(addr),
+            4 => Call(addr),
+            5 => SkipIfEqual(x, byte),
+            6 => SkipNotEqual(x, byte),
+            7 => SkipRegistersEqual(x, y),
+            8 => LoadRegByte(x, byte),
+            9 => Add(x, byte),
+            10 => CopyRegToReg { x, y },
+            11 => Or { x, y },
+            12 => And { x, y },
+            13 => Xor { x, y },
+            14 => AddReg { x, y },
+            15 => Sub { x, y },
+            16 => Shr { x, y },
+            17 => SubN { x, y },
+            18 => Shl { x, y },
+            19 => SkipRegistersNotEqual(x, y),
+            20 => LoadImmediate(addr),
+            21 => JumpRelative(addr),
+            22 => Random(x, byte),
+            23 => DisplaySprite {
+                x,
+                y,
+                number_of_bytes: rng.gen_range(0..16),
+            },
+            24 => SkipPressed(x),
+            25 => SkipNotPressed(x),
+            26 => GetDelayTimer(x),
+            27 => WaitForKey(x),
+            28 => SetDelayTimer(x),
+            29 => SetSoundTimer(x),
+            30 => AddI(x),
+            31 => GetSprite(x),
+            32 => BCD(x),
+            33 => WriteMultiple(x),
+            34 => ReadMultiple(x),
+            // Above 0x0EE, as 0x000, 0x0E0 and 0x0EE encode `nop`, `cls` and `ret`
+            35 => Sys(Address::new(rng.gen_range(0x100..=Address::MAX.as_u16()))),
+            36 => SelectPlanes(rng.gen_range(0..4)),
+            _ => unreachable!("only {} variants", VARIANTS),
+        }
+    }
+
+    #[test]
+    fn test_trailing_tokens_flagged() {
+        let error = assemble_str("ret 5\n").unwrap_err();
+        match error {
+            AssembleError::Compile {
+                error:
+                    CompilationError::InstructionError(InstructionError::TrailingTokens { at, .. }),
+                ..
+            } => assert_eq!((at.offset(), at.len()), (4, 1)),
+            other => panic!("expected trailing tokens, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_round_trip_through_source() {
+        let mut rng = StdRng::seed_from_u64(0xC8);
+        let mut seen = HashSet::new();
+        for _ in 0..20 {
+            let instructions = (0..VARIANTS)
+                .map(|variant| arbitrary_instruction(&mut rng, variant))
+                .collect::<Vec<_>>();
+            seen.extend(instructions.iter().map(discriminant));
+
+            let source = instructions
+                .iter()
+                .map(|instruction| instruction.to_asm() + "\n")
+                .collect::<String>();
+            let rom = assemble_with(&source, None, Target::XoChip)
+                .unwrap_or_else(|e| panic!("{:?}\n{}", e, source));
+            for (i, instruction) in instructions.iter().enumerate() {
+                let raw = instruction.to_data();
+                let offset = i as u16 * 2;
+                assert_eq!(
+                    (rom[Address::new(offset)], rom[Address::new(offset + 1)]),
+                    (raw.first(), raw.second()),
+                    "{}",
+                    instruction.to_asm()
+                );
+            }
+        }
+        assert_eq!(seen.len(), VARIANTS);
+    }
+
+    #[test]
+    fn test_assemble_str() {
+        let rom = assemble_str("cls\nld v0, 0x12\nloop:\njp loop\n").unwrap();
+        let bytes = (0..6)
+            .map(|offset| rom[Address::new(offset)].0)
+            .collect::<Vec<_>>();
+        assert_eq!(bytes, [0x00, 0xE0, 0x60, 0x12, 0x12, 0x04]);
+    }
+
+    #[test]
+    fn test_consecutive_labels_share_address() {
+        let rom =
+            assemble_str("cls\nfirst:\nsecond:\ndrw v0, v1, 5\njp first\njp second\n").unwrap();
+        let bytes = (0..8).map(|i| rom[Address::new(i)].0).collect::<Vec<_>>();
+        assert_eq!(bytes, [0x00, 0xE0, 0xD0, 0x15, 0x12, 0x02, 0x12, 0x02]);
+
+        let rom = assemble_str("cls\nx1: x2: ret\ntable:\n.data 1, 2\njp x2\njp table\n").unwrap();
+        let bytes = (0..10).map(|i| rom[Address::new(i)].0).collect::<Vec<_>>();
+        assert_eq!(
+            bytes,
+            [0x00, 0xE0, 0x00, 0xEE, 0x01, 0x02, 0x12, 0x02, 0x12, 0x04]
+        );
+    }
+
+    #[test]
+    fn test_relative_jumps() {
+        let rom = assemble_str("cls\nloop:\nadd v0, 1\nrel loop\nrel 4\ncls\nret\n").unwrap();
+        let jumps = [4, 6].map(|i| (rom[Address::new(i)].0, rom[Address::new(i + 1)].0));
+        assert_eq!(jumps, [(0x12, 0x02), (0x12, 0x0A)]);
+
+        let rom = assemble_str("cls\nadd v0, 1\nrel -2\nrel +2\n").unwrap();
+        let jumps = [4, 6].map(|i| (rom[Address::new(i)].0, rom[Address::new(i + 1)].0));
+        assert_eq!(jumps, [(0x12, 0x02), (0x12, 0x08)]);
+        assert!(assemble_str("rel -0x201\n").is_err());
+        assert!(assemble_str("jp -2\n").is_err());
+    }
+
+    #[test]
+    fn test_error_codes() {
+        use miette::Diagnostic;
+
+        let code = |source: &str| {
+            assemble_str(source)
+                .unwrap_err()
+                .code()
+                .unwrap()
+                .to_string()
+        };
+        assert_eq!(code("ld v0, #\n"), "c8asm::tokenize::unrecognised_item");
+        assert_eq!(
+            code(".nope\n"),
+            "c8asm::parse::invocation::unknown_invocation"
+        );
+        assert_eq!(code(".if 1\ncls\n"), "c8asm::parse::conditional::unclosed");
+        assert_eq!(
+            code("jp nowhere\n"),
+            "c8asm::compile::instruction::missing_label"
+        );
+        assert_eq!(
+            code("$a 1\n$a 2\n"),
+            "c8asm::compile::value::rebound_constant"
+        );
+    }
+
+    #[test]
+    fn test_error_stages() {
+        use miette::Diagnostic;
+
+        for (source, stage) in [
+            ("ld v0, #\n", "Tokenize"),
+            (".nope\n", "Parse"),
+            ("jp nowhere\n", "Compile"),
+        ] {
+            let error = assemble_str(source).unwrap_err();
+            let (variant, source_code) = match &error {
+                AssembleError::Tokenize { source_code, .. } => ("Tokenize", source_code),
+                AssembleError::Parse { source_code, .. } => ("Parse", source_code),
+                AssembleError::Compile { source_code, .. } => ("Compile", source_code),
+            };
+            assert_eq!(variant, stage, "for {:?}", source);
+            assert_eq!(source_code, source);
+            let span = error.labels().unwrap().next().unwrap();
+            assert!(span.offset() + span.len() <= source.len());
+        }
+    }
+
+    #[test]
+    fn test_register_alias() {
+        let first_two = |source: &str| {
+            let rom = assemble_str(source).unwrap();
+            (rom[Address::new(0)].0, rom[Address::new(1)].0)
+        };
+        assert_eq!(
+            first_two(".name counter = v3\nadd counter, 1\n"),
+            (0x73, 0x01)
+        );
+        assert_eq!(
+            first_two(".name counter = v3\nadd .counter, 1\n"),
+            (0x73, 0x01)
+        );
+        assert_eq!(
+            first_two(".name x = v1, y = v2\ndrw x, y, 5\n"),
+            (0xD1, 0x25)
+        );
+    }
+
+    #[test]
+    fn test_label_alias() {
+        let rom = assemble_str(
+            ".name ptr = sprite\n.name again = .ptr\nld i, ptr\nld i, .again\nend:\njp end\nsprite:\n.data 0xF0\n",
+        )
+        .unwrap();
+        let bytes = (0..4)
+            .map(|offset| rom[Address::new(offset)].0)
+            .collect::<Vec<_>>();
+        assert_eq!(bytes, [0xA2, 0x06, 0xA2, 0x06]);
+    }
+
+    #[test]
+    fn test_reference_mismatches() {
+        let rom = assemble_str("cls\nld v0, 0x12\n").unwrap();
+        let reference = ROM::from_bytes(vec![0x00, 0xE0, 0x60, 0x12]).unwrap();
+        assert!(reference_mismatches(&rom, &reference).is_empty());
+
+        let reference = ROM::from_bytes(vec![0x00, 0xE0, 0x60, 0x13, 0x00, 0x00]).unwrap();
+        assert_eq!(
+            reference_mismatches(&rom, &reference),
+            ["0x003: expected 13, got 12"]
+        );
+        let reference = ROM::from_bytes(vec![0x00, 0xE0, 0x60, 0x12, 0x00, 0xEE]).unwrap();
+        assert_eq!(
+            reference_mismatches(&rom, &reference),
+            ["0x005: expected EE, got 00"]
+        );
+    }
+
+    #[test]
+    fn test_saved_reference_matches() {
+        let rom = assemble_str("cls\nld v0, 0x12\n").unwrap();
+        let path =
+            std::env::temp_dir().join(format!("c8asm_saved_reference_{}.ch8", std::process::id()));
+        rom.save(&path).unwrap();
+        let reference = ROM::from_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(reference_mismatches(&rom, &reference).is_empty());
+    }
+
+    #[test]
+    fn test_source_from_stdin() {
+        let (source, base_dir) = read_source(STDIO_PATH, "cls\nld v0, 0x12\n".as_bytes()).unwrap();
+        assert_eq!(base_dir, Path::new("."));
+        let rom = assemble_with(&source, Some(&base_dir), Target::Chip8).unwrap();
+        assert_eq!(rom.program_len(), 4);
+
+        let (source, _) = read_source(STDIO_PATH, "cls\nfoo v0\n".as_bytes()).unwrap();
+        match assemble_with(&source, Some(&base_dir), Target::Chip8) {
+            Err(AssembleError::Compile { source_code, .. }) => assert_eq!(source_code, source),
+            other => panic!("expected a compile error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_targets() {
+        let source = "scd 4\nscr\nplane 3\n";
+        let unknown = |target| match assemble_with(source, None, target).unwrap_err() {
+            AssembleError::Compile {
+                error:
+                    CompilationError::InstructionError(InstructionError::UnknownInstruction {
+                        opcode,
+                        ..
+                    }),
+                ..
+            } => opcode,
+            other => panic!("expected an unknown instruction, got {:?}", other),
+        };
+        assert_eq!(unknown(Target::Chip8), "scd");
+        assert_eq!(unknown(Target::SuperChip), "plane");
+
+        let rom = assemble_with(source, None, Target::XoChip).unwrap();
+        let bytes = (0..6)
+            .map(|offset| rom[Address::new(offset)].0)
+            .collect::<Vec<_>>();
+        assert_eq!(bytes, [0x00, 0xC4, 0x00, 0xFB, 0xF3, 0x01]);
+        assert_eq!("schip".parse::<Target>().unwrap(), Target::SuperChip);
+        assert!("superchip".parse::<Target>().is_err());
+    }
 }
@@ -1,37 +1,262 @@
 use crate::tokenizing::{Item, Lexical, Punct, Spanned};
-use error::*;
+use c8common::{Address, GeneralRegister, NUMBER_OF_ADDRESSES};
+pub use error::*;
 use miette::SourceSpan;
+use std::collections::HashMap;
 use std::iter::Peekable;
+use std::path::PathBuf;
 
+/// `.incbin` paths are taken relative to the working directory, see `parse_relative_to`
 pub fn parse(tokens: Vec<Spanned<Item>>) -> Result<Vec<Spanned<ExecutionItem>>, ConversionError> {
     Parser::new(tokens.into_iter()).convert()
 }
 
+/// Like `parse`, with `.incbin` paths taken relative to `base_dir`, usually the source file's
+/// directory
+pub fn parse_relative_to(
+    tokens: Vec<Spanned<Item>>,
+    base_dir: impl Into<PathBuf>,
+) -> Result<Vec<Spanned<ExecutionItem>>, ConversionError> {
+    let mut parser = Parser::new(tokens.into_iter());
+    parser.base_dir = Some(base_dir.into());
+    parser.convert()
+}
+
 struct Parser<T: Iterator<Item = Spanned<Item>>> {
     tokens: Peekable<T>,
+    base_dir: Option<PathBuf>,
+    /// Constants seen so far, with their value if it could be resolved while parsing
+    constants: HashMap<String, Option<u16>>,
+    conditionals: Vec<Conditional>,
+}
+
+/// An open `.if`/`.ifdef` block
+#[derive(Debug, Copy, Clone)]
+struct Conditional {
+    opened_at: SourceSpan,
+    /// Whether the enclosing lines are being included
+    parent_active: bool,
+    condition: bool,
+    seen_else: bool,
+}
+
+impl Conditional {
+    fn active(&self) -> bool {
+        self.parent_active && (self.condition != self.seen_else)
+    }
+}
+
+/// The operator in an `.if` condition such as `.if $LEVEL >= 2`
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Comparison {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+impl Comparison {
+    fn compare(self, left: u16, right: u16) -> bool {
+        match self {
+            Self::Equal => left == right,
+            Self::NotEqual => left != right,
+            Self::Less => left < right,
+            Self::LessOrEqual => left <= right,
+            Self::Greater => left > right,
+            Self::GreaterOrEqual => left >= right,
+        }
+    }
 }
 
 impl<T: Iterator<Item = Spanned<Item>>> Parser<T> {
     fn new(tokens: T) -> Self {
         Parser {
             tokens: tokens.peekable(),
+            base_dir: None,
+            constants: HashMap::new(),
+            conditionals: vec![],
         }
     }
 
     fn convert(mut self) -> Result<Vec<Spanned<ExecutionItem>>, ConversionError> {
         let mut output = vec![];
         loop {
-            let line = match self.get_line() {
+            let mut line = match self.get_line() {
                 Some(line) => line,
                 None => {
-                    return Ok(output);
+                    return match self.conditionals.last() {
+                        Some(open) => Err(ConditionalError::unclosed(open.opened_at))?,
+                        None => Ok(output),
+                    };
                 }
             };
+            if let Some((directive, at)) = Self::take_conditional_directive(&mut line) {
+                self.apply_conditional(&directive, at, line)?;
+                continue;
+            }
+            if !self.including() {
+                continue;
+            }
             let action = self.parse_line(line)?;
+            if let ExecutionItem::DefineConstant { name, value } = &action.item {
+                let resolved = self.resolve(value);
+                self.constants.insert(name.clone(), resolved);
+            }
             output.push(action);
         }
     }
 
+    fn including(&self) -> bool {
+        self.conditionals
+            .last()
+            .map(Conditional::active)
+            .unwrap_or(true)
+    }
+
+    fn resolve(&self, value: &Value) -> Option<u16> {
+        match value {
+            Value::Numeric(number) => Some(*number),
+            Value::Constant(name) => self.constants.get(name).copied().flatten(),
+            _ => None,
+        }
+    }
+
+    /// Consumes the first token of the line if it is one of the conditional assembly directives
+    fn take_conditional_directive<S: Iterator<Item = Spanned<Item>>>(
+        line: &mut Peekable<S>,
+    ) -> Option<(String, SourceSpan)> {
+        let directive = match line.peek().map(|s| &s.item) {
+            Some(Item::Lexical(Lexical::PrefixedIdent(Punct::Period, ident))) => {
+                ident.to_ascii_lowercase()
+            }
+            _ => return None,
+        };
+        if !matches!(&directive[..], "if" | "ifdef" | "else" | "endif") {
+            return None;
+        }
+        let at = line.next().expect("known to exist by peeking").at;
+        Some((directive, at))
+    }
+
+    fn apply_conditional<S: Iterator<Item = Spanned<Item>>>(
+        &mut self,
+        directive: &str,
+        at: SourceSpan,
+        mut line: Peekable<S>,
+    ) -> Result<(), ConditionalError> {
+        match directive {
+            "if" | "ifdef" => {
+                let parent_active = self.including();
+                let Spanned {
+                    item: operand,
+                    at: operand_at,
+                } = line
+                    .next()
+                    .ok_or_else(|| ConditionalError::missing_condition(at))?;
+                let condition = if directive == "ifdef" {
+                    let name = match operand {
+                        Item::Lexical(Lexical::PrefixedIdent(Punct::Dollar, name))
+                        | Item::Lexical(Lexical::Ident(name)) => name,
+                        _ => Err(ConditionalError::condition_type(operand_at))?,
+                    };
+                    parent_active && self.constants.contains_key(&name)
+                } else {
+                    let left = Self::condition_operand(operand, operand_at)?;
+                    let comparison = Self::take_comparison(&mut line)?;
+                    let right = match comparison {
+                        Some((_, operator_at)) => {
+                            let Spanned { item, at } = line
+                                .next()
+                                .ok_or_else(|| ConditionalError::missing_condition(operator_at))?;
+                            Some(Self::condition_operand(item, at)?)
+                        }
+                        None => None,
+                    };
+                    // Nothing in an excluded block is included, so its values needn't be known
+                    parent_active && {
+                        let left = self.resolve_condition(left)?;
+                        match (comparison, right) {
+                            (Some((comparison, _)), Some(right)) => {
+                                comparison.compare(left, self.resolve_condition(right)?)
+                            }
+                            _ => left != 0,
+                        }
+                    }
+                };
+                self.conditionals.push(Conditional {
+                    opened_at: at,
+                    parent_active,
+                    condition,
+                    seen_else: false,
+                });
+            }
+            "else" => {
+                let open = self
+                    .conditionals
+                    .last_mut()
+                    .ok_or_else(|| ConditionalError::unmatched(at))?;
+                if open.seen_else {
+                    Err(ConditionalError::duplicate_else(at))?
+                }
+                open.seen_else = true;
+            }
+            "endif" => {
+                self.conditionals
+                    .pop()
+                    .ok_or_else(|| ConditionalError::unmatched(at))?;
+            }
+            _ => unreachable!("only conditional directives are taken"),
+        }
+        let rest = line.collect::<Vec<_>>();
+        match Self::get_total_span(&rest) {
+            Some(extra) => Err(ConditionalError::too_many(extra)),
+            None => Ok(()),
+        }
+    }
+
+    fn condition_operand(item: Item, at: SourceSpan) -> Result<Spanned<Value>, ConditionalError> {
+        let value = match item {
+            Item::Lexical(Lexical::Numeric(number)) => Value::Numeric(number),
+            Item::Lexical(Lexical::PrefixedIdent(Punct::Dollar, name)) => Value::Constant(name),
+            _ => Err(ConditionalError::condition_type(at))?,
+        };
+        Ok(Spanned::new(value, at))
+    }
+
+    fn resolve_condition(&self, value: Spanned<Value>) -> Result<u16, ConditionalError> {
+        self.resolve(&value.item)
+            .ok_or_else(|| ConditionalError::unknown_value(value.at))
+    }
+
+    /// Consumes a comparison operator such as `==` or `<=` if one comes next
+    fn take_comparison<S: Iterator<Item = Spanned<Item>>>(
+        line: &mut Peekable<S>,
+    ) -> Result<Option<(Comparison, SourceSpan)>, ConditionalError> {
+        let (first, at) = match line.peek() {
+            Some(Spanned {
+                item: Item::Punct(punct),
+                at,
+            }) => (*punct, *at),
+            _ => return Ok(None),
+        };
+        line.next();
+        let followed_by_equals = line
+            .next_if(|next| next.item == Item::Punct(Punct::Equals))
+            .is_some();
+        let comparison = match (first, followed_by_equals) {
+            (Punct::Equals, true) => Comparison::Equal,
+            (Punct::Bang, true) => Comparison::NotEqual,
+            (Punct::Less, false) => Comparison::Less,
+            (Punct::Less, true) => Comparison::LessOrEqual,
+            (Punct::Greater, false) => Comparison::Greater,
+            (Punct::Greater, true) => Comparison::GreaterOrEqual,
+            _ => Err(ConditionalError::unknown_comparison(at))?,
+        };
+        Ok(Some((comparison, at)))
+    }
+
     fn get_line(&mut self) -> Option<Peekable<std::vec::IntoIter<Spanned<Item>>>> {
         let mut line = vec![];
         let mut linebroken = false;
@@ -55,7 +280,7 @@ impl<T: Iterator<Item = Spanned<Item>>> Parser<T> {
     ) -> Result<Spanned<ExecutionItem>, ConversionError> {
         match line.peek() {
             Some(first) => match first.item {
-                Item::Lexical(Lexical::PrefixedIdent(_, _)) => Ok(Self::parse_line_internal(line)?),
+                Item::Lexical(Lexical::PrefixedIdent(_, _)) => Ok(self.parse_line_internal(line)?),
                 Item::Lexical(Lexical::Ident(_)) => {
                     let first = line.next().expect("known to exist by peeking");
                     if matches!(
@@ -69,6 +294,9 @@ impl<T: Iterator<Item = Spanned<Item>>> Parser<T> {
                             .expect("known correct by match")
                             .to_ident()
                             .expect("known correct by match");
+                        if GeneralRegister::from_name(&label).is_some() {
+                            return Err(ConversionError::register_label(label, first.at));
+                        }
                         return Ok(ExecutionItem::Label(Label::Direct(label)).spanned(first.at));
                     }
                     // This is an instruction
@@ -89,7 +317,9 @@ impl<T: Iterator<Item = Spanned<Item>>> Parser<T> {
                 Item::Lexical(Lexical::Numeric(_)) => Err(DataDefinitionError::exposed_data(
                     Self::get_total_span(&line.collect::<Vec<_>>()[..]).expect("the span exists"),
                 ))?,
-                Item::Punct(_) => Err(ConversionError::no_rules(first.at)),
+                Item::Lexical(Lexical::Str(_)) | Item::Punct(_) => {
+                    Err(ConversionError::no_rules(first.at))
+                }
                 Item::Linebreak => Ok(ExecutionItem::Nothing.spanned(first.at)),
             },
             None => Ok(ExecutionItem::Nothing.spanned((0, 0).into())),
@@ -98,6 +328,7 @@ impl<T: Iterator<Item = Spanned<Item>>> Parser<T> {
 
     fn add_raw_data<S: Iterator<Item = Spanned<Item>>>(
         line: Peekable<S>,
+        width: DataWidth,
     ) -> Result<Spanned<ExecutionItem>, DataDefinitionError> {
         let mut data = vec![];
         let mut expecting_number = true;
@@ -111,8 +342,11 @@ impl<T: Iterator<Item = Spanned<Item>>> Parser<T> {
                     .to_lexical()
                     .and_then(|i| i.as_numeric())
                     .ok_or_else(|| DataDefinitionError::data_entry(at, true))?;
-                let number = Self::parse_datum(number, at)?;
-                data.push(number);
+                match width {
+                    DataWidth::Byte => data.push(Self::parse_datum(number, at)?),
+                    DataWidth::BigEndianWord => data.extend(number.to_be_bytes()),
+                    DataWidth::LittleEndianWord => data.extend(number.to_le_bytes()),
+                }
                 expecting_number = false;
             } else if item.as_punct().map(|p| p == Punct::Comma) != Some(true) {
                 Err(DataDefinitionError::data_entry(at, false))?
@@ -127,6 +361,38 @@ impl<T: Iterator<Item = Spanned<Item>>> Parser<T> {
         Ok(ExecutionItem::RawData(data).spanned(total_span.unwrap_or_else(|| (0, 0).into())))
     }
 
+    fn include_binary<S: Iterator<Item = Spanned<Item>>>(
+        &self,
+        invocation_at: SourceSpan,
+        mut line: Peekable<S>,
+    ) -> Result<Spanned<ExecutionItem>, IncludeBinaryError> {
+        let Spanned { item, at } = line
+            .next()
+            .ok_or_else(|| IncludeBinaryError::missing_path(invocation_at))?;
+        let path = item
+            .to_lexical()
+            .and_then(Lexical::to_str)
+            .ok_or_else(|| IncludeBinaryError::path_type(at))?;
+        let rest = line.collect::<Vec<_>>();
+        if let Some(extra) = Self::get_total_span(&rest) {
+            Err(IncludeBinaryError::too_many(extra))?
+        }
+
+        let full_path = match &self.base_dir {
+            Some(base_dir) => base_dir.join(&path),
+            None => PathBuf::from(&path),
+        };
+        let read_error = |error: std::io::Error| IncludeBinaryError::read(&full_path, error, at);
+        // Checked before reading, so pointing at something huge fails quickly
+        let size = std::fs::metadata(&full_path).map_err(read_error)?.len();
+        let capacity = NUMBER_OF_ADDRESSES - Address::PROGRAM_START_INDEX;
+        if size > capacity as u64 {
+            Err(IncludeBinaryError::too_large(size, capacity, at))?
+        }
+        let data = std::fs::read(&full_path).map_err(read_error)?;
+        Ok(ExecutionItem::RawData(data).spanned(long_span(invocation_at, at)))
+    }
+
     fn parse_datum(from: u16, at: SourceSpan) -> Result<u8, DataDefinitionError> {
         from.try_into()
             .map_err(|_| DataDefinitionError::number_too_big(from, at))
@@ -134,7 +400,7 @@ impl<T: Iterator<Item = Spanned<Item>>> Parser<T> {
 
     fn get_instruction_arguments<S: Iterator<Item = Spanned<Item>>>(
         mut line: Peekable<S>,
-    ) -> Result<Spanned<Vec<Value>>, InstructionError> {
+    ) -> Result<Spanned<Vec<Spanned<Value>>>, InstructionError> {
         let mut args = vec![];
         let mut expects_comma = false;
 
@@ -163,21 +429,44 @@ impl<T: Iterator<Item = Spanned<Item>>> Parser<T> {
             if first_span.is_none() {
                 first_span = Some(at);
             }
+            let (value, at) = match item {
+                Item::Punct(sign @ (Punct::Minus | Punct::Plus)) => {
+                    let number = line
+                        .next()
+                        .ok_or_else(|| InstructionError::invalid_arg_type(at))?;
+                    let at = long_span(at, number.at);
+                    let number = number
+                        .item
+                        .as_lexical()
+                        .and_then(Lexical::as_numeric)
+                        .ok_or_else(|| InstructionError::invalid_arg_type(at))?;
+                    let value = if sign == Punct::Minus {
+                        Value::Negative(number)
+                    } else {
+                        Value::Numeric(number)
+                    };
+                    (value, at)
+                }
+                item => {
+                    let item = item
+                        .to_lexical()
+                        .ok_or_else(|| InstructionError::invalid_arg_type(at))?;
+                    let value = match item {
+                        Lexical::PrefixedIdent(prefix, ident) => Some(match prefix {
+                            Punct::Period => Value::Local(ident),
+                            Punct::Dollar => Value::Constant(ident),
+                            _ => panic!("Only `Period` and `Dollar` can be used as prefixes!"),
+                        }),
+                        Lexical::Numeric(num) => Some(Value::Numeric(num)),
+                        Lexical::Ident(ident) => Some(Value::name_or_label(ident)),
+                        Lexical::Str(_) => None,
+                    }
+                    .ok_or_else(|| InstructionError::invalid_arg_type(at))?;
+                    (value, at)
+                }
+            };
             last_span = Some(at);
-            let item = item
-                .to_lexical()
-                .ok_or_else(|| InstructionError::invalid_arg_type(at))?;
-            let value = match item {
-                Lexical::PrefixedIdent(prefix, ident) => Some(match prefix {
-                    Punct::Period => Value::Local(ident),
-                    Punct::Dollar => Value::Constant(ident),
-                    _ => panic!("Only `Period` and `Dollar` can be used as prefixes!"),
-                }),
-                Lexical::Numeric(num) => Some(Value::Numeric(num)),
-                Lexical::Ident(ident) => Some(Value::name_or_label(ident)),
-            }
-            .ok_or_else(|| InstructionError::invalid_arg_type(at))?;
-            args.push(value);
+            args.push(value.spanned(at));
         }
 
         Ok(Spanned {
@@ -190,6 +479,7 @@ impl<T: Iterator<Item = Spanned<Item>>> Parser<T> {
     }
 
     fn parse_line_internal<S: Iterator<Item = Spanned<Item>>>(
+        &self,
         mut line: Peekable<S>,
     ) -> Result<Spanned<ExecutionItem>, InvocationError> {
         let token = line.next().expect("this is known to exist by peeking");
@@ -201,18 +491,19 @@ impl<T: Iterator<Item = Spanned<Item>>> Parser<T> {
             .to_prefixed()
             .expect("known to be a prefixed ident");
         match punct {
-            Punct::Period => Self::parse_internal_item(ident, invocation_at, line),
+            Punct::Period => self.parse_internal_item(ident, invocation_at, line),
             Punct::Dollar => {
                 // Dollar item, first token of line => constant assignment
+                if GeneralRegister::from_name(&ident).is_some() {
+                    return Err(ConstantDefinitionError::register_name(ident, invocation_at).into());
+                }
                 let Spanned { item: value, at } = line
                     .next()
                     .ok_or_else(|| ConstantDefinitionError::constant_needs_value(invocation_at))?;
                 let value: Value = {
                     match value {
                         Item::Lexical(Lexical::Numeric(number)) => Ok(Value::Numeric(number)),
-                        Item::Lexical(Lexical::PrefixedIdent(prefix, ident))
-                            if prefix == Punct::Dollar =>
-                        {
+                        Item::Lexical(Lexical::PrefixedIdent(Punct::Dollar, ident)) => {
                             Ok(Value::Constant(ident))
                         }
                         _ => Err(ConstantDefinitionError::constant_value_type(at)),
@@ -226,13 +517,17 @@ impl<T: Iterator<Item = Spanned<Item>>> Parser<T> {
     }
 
     fn parse_internal_item<S: Iterator<Item = Spanned<Item>>>(
+        &self,
         mut invocation: String,
         invocation_at: SourceSpan,
         mut line: Peekable<S>,
     ) -> Result<Spanned<ExecutionItem>, InvocationError> {
         invocation.make_ascii_lowercase();
         match &invocation[..] {
-            "data" => Ok(Self::add_raw_data(line)?),
+            "data" => Ok(Self::add_raw_data(line, DataWidth::Byte)?),
+            "dw" => Ok(Self::add_raw_data(line, DataWidth::BigEndianWord)?),
+            "dw_le" => Ok(Self::add_raw_data(line, DataWidth::LittleEndianWord)?),
+            "incbin" => Ok(self.include_binary(invocation_at, line)?),
             "name" => {
                 let mut bindings: Vec<LocalBinding> = vec![];
                 let mut expects_comma = false;
@@ -288,6 +583,7 @@ impl<T: Iterator<Item = Spanned<Item>>> Parser<T> {
                         }),
                         Lexical::Numeric(num) => Some(Value::Numeric(num)),
                         Lexical::Ident(ident) => Some(Value::name_or_label(ident)),
+                        Lexical::Str(_) => None,
                     }
                     .ok_or_else(|| NameDefinitionError::invalid_value_type(at))?;
                     bindings.push(LocalBinding { name, value });
@@ -331,6 +627,20 @@ impl<T: Iterator<Item = Spanned<Item>>> Parser<T> {
                     ))?
                 }
             }
+            "entry" => {
+                let Spanned { item, at } = line
+                    .next()
+                    .ok_or_else(|| EntryDefinitionError::missing_label(invocation_at))?;
+                let label = item
+                    .to_lexical()
+                    .and_then(Lexical::to_ident)
+                    .ok_or_else(|| EntryDefinitionError::label_type(at))?;
+                let rest = line.collect::<Vec<_>>();
+                if let Some(extra) = Self::get_total_span(&rest) {
+                    Err(EntryDefinitionError::too_many(extra))?
+                }
+                Ok(ExecutionItem::Label(Label::Entry(label)).spanned(long_span(invocation_at, at)))
+            }
             _ => Err(InvocationError::unknown_invocation(invocation_at))?,
         }
     }
@@ -372,6 +682,8 @@ impl TryFrom<Spanned<Item>> for Spanned<Ident> {
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum Value {
     Numeric(u16),
+    /// A number written with a leading `-`, such as the offset in `rel -4`
+    Negative(u16),
     Constant(String),
     Local(String),
     Name(ReservedName),
@@ -422,7 +734,7 @@ pub enum ExecutionItem {
     BindLocal(Vec<LocalBinding>),
     Instruction {
         opcode: String,
-        arguments: Vec<Value>,
+        arguments: Vec<Spanned<Value>>,
     },
     Label(Label),
     RawData(Vec<u8>),
@@ -439,6 +751,8 @@ pub enum Label {
     Direct(String),
     /// The Value can only be numeric or a constant
     AssertAddress(Value),
+    /// Where the program should start running, rather than at its first byte
+    Entry(String),
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -449,16 +763,28 @@ pub struct LocalBinding {
 
 mod error {
     use miette::{Diagnostic, SourceSpan};
+    use std::path::{Path, PathBuf};
     use tap::Conv;
     use thiserror::Error;
 
     #[derive(Debug, Error, Diagnostic)]
     pub enum ConversionError {
         #[error("No rules expected this token")]
+        #[diagnostic(code(c8asm::parse::no_rules))]
         NoRules {
             #[label("here")]
             at: SourceSpan,
         },
+        #[error("Label '{}' has the name of a register", .name)]
+        #[diagnostic(
+            code(c8asm::parse::register_label),
+            help("`v0` to `vf` always refer to registers, so pick another name")
+        )]
+        RegisterLabel {
+            name: String,
+            #[label("here")]
+            at: SourceSpan,
+        },
 
         #[error(transparent)]
         #[diagnostic(transparent)]
@@ -466,12 +792,19 @@ mod error {
         #[error(transparent)]
         #[diagnostic(transparent)]
         Invocation(#[from] InvocationError),
+        #[error(transparent)]
+        #[diagnostic(transparent)]
+        Conditional(#[from] ConditionalError),
     }
 
     impl ConversionError {
         pub(super) fn no_rules(at: SourceSpan) -> Self {
             Self::NoRules { at }
         }
+
+        pub(super) fn register_label(name: String, at: SourceSpan) -> Self {
+            Self::RegisterLabel { name, at }
+        }
     }
 
     impl InstructionError {
@@ -498,6 +831,10 @@ mod error {
         pub(super) fn constant_value_type(value: SourceSpan) -> Self {
             Self::ConstantValueType { value }
         }
+
+        pub(super) fn register_name(name: String, constant: SourceSpan) -> Self {
+            Self::RegisterName { name, constant }
+        }
     }
 
     impl DataDefinitionError {
@@ -523,13 +860,57 @@ mod error {
                 number,
                 at,
                 help: format!(
-                    "Try splitting into two bytes: 0x{:02X}, 0x{:02X}",
+                    "Try splitting into two bytes: 0x{:02X}, 0x{:02X}, or use `.dw`",
                     high, low
                 ),
             }
         }
     }
 
+    impl IncludeBinaryError {
+        pub(super) fn missing_path(after: SourceSpan) -> Self {
+            Self::MissingPath { after }
+        }
+
+        pub(super) fn path_type(at: SourceSpan) -> Self {
+            Self::PathType { at }
+        }
+
+        pub(super) fn too_many(at: SourceSpan) -> Self {
+            Self::TooMany { at }
+        }
+
+        pub(super) fn read(path: &Path, error: std::io::Error, at: SourceSpan) -> Self {
+            Self::Read {
+                path: path.to_path_buf(),
+                reason: error.to_string(),
+                at,
+            }
+        }
+
+        pub(super) fn too_large(size: u64, capacity: usize, at: SourceSpan) -> Self {
+            Self::TooLarge {
+                size,
+                help: format!("at most {} bytes fit after 0x200", capacity),
+                at,
+            }
+        }
+    }
+
+    impl EntryDefinitionError {
+        pub(super) fn missing_label(after: SourceSpan) -> Self {
+            Self::MissingLabel { after }
+        }
+
+        pub(super) fn label_type(at: SourceSpan) -> Self {
+            Self::LabelType { at }
+        }
+
+        pub(super) fn too_many(at: SourceSpan) -> Self {
+            Self::TooMany { at }
+        }
+    }
+
     impl AssertDefinitionError {
         pub(super) fn assert_missing_addr(at: SourceSpan) -> Self {
             Self::MissingAddr { at }
@@ -570,14 +951,50 @@ mod error {
         }
     }
 
+    impl ConditionalError {
+        pub(super) fn unclosed(at: SourceSpan) -> Self {
+            Self::Unclosed { at }
+        }
+
+        pub(super) fn unmatched(at: SourceSpan) -> Self {
+            Self::Unmatched { at }
+        }
+
+        pub(super) fn duplicate_else(at: SourceSpan) -> Self {
+            Self::DuplicateElse { at }
+        }
+
+        pub(super) fn missing_condition(after: SourceSpan) -> Self {
+            Self::MissingCondition { after }
+        }
+
+        pub(super) fn condition_type(at: SourceSpan) -> Self {
+            Self::ConditionType { at }
+        }
+
+        pub(super) fn unknown_value(at: SourceSpan) -> Self {
+            Self::UnknownValue { at }
+        }
+
+        pub(super) fn too_many(at: SourceSpan) -> Self {
+            Self::TooMany { at }
+        }
+
+        pub(super) fn unknown_comparison(at: SourceSpan) -> Self {
+            Self::UnknownComparison { at }
+        }
+    }
+
     #[derive(Debug, Error, Diagnostic)]
     pub enum InstructionError {
         #[error("Expected a comma")]
+        #[diagnostic(code(c8asm::parse::instruction::expected_comma))]
         ExpectedComma {
             #[label("here")]
             at: SourceSpan,
         },
         #[error("Invalid type for arguments")]
+        #[diagnostic(code(c8asm::parse::instruction::invalid_arg_type))]
         InvalidArgType {
             #[label("here")]
             at: SourceSpan,
@@ -587,7 +1004,12 @@ mod error {
     #[derive(Debug, Error, Diagnostic)]
     pub enum InvocationError {
         #[error("Unknown invocation")]
-        #[diagnostic(help("try one of `name`, `data` or `assert_addr`"))]
+        #[diagnostic(
+            code(c8asm::parse::invocation::unknown_invocation),
+            help(
+                "try one of `name`, `data`, `dw`, `dw_le`, `incbin`, `assert_addr`, `entry`, `if` or `ifdef`"
+            )
+        )]
         UnknownInvocation {
             #[label("here")]
             at: SourceSpan,
@@ -605,25 +1027,113 @@ mod error {
         #[error(transparent)]
         #[diagnostic(transparent)]
         Name(#[from] NameDefinitionError),
+        #[error(transparent)]
+        #[diagnostic(transparent)]
+        IncludeBinary(#[from] IncludeBinaryError),
+        #[error(transparent)]
+        #[diagnostic(transparent)]
+        Entry(#[from] EntryDefinitionError),
+    }
+
+    #[derive(Debug, Error, Diagnostic)]
+    pub enum ConditionalError {
+        #[error("This conditional block is never closed")]
+        #[diagnostic(
+            code(c8asm::parse::conditional::unclosed),
+            help("add a matching `.endif`")
+        )]
+        Unclosed {
+            #[label("opened here")]
+            at: SourceSpan,
+        },
+        #[error("There is no open conditional block")]
+        #[diagnostic(
+            code(c8asm::parse::conditional::unmatched),
+            help("start one with `.if` or `.ifdef`")
+        )]
+        Unmatched {
+            #[label("here")]
+            at: SourceSpan,
+        },
+        #[error("A conditional block can only have one `.else`")]
+        #[diagnostic(code(c8asm::parse::conditional::duplicate_else))]
+        DuplicateElse {
+            #[label("here")]
+            at: SourceSpan,
+        },
+        #[error("Conditional blocks require a condition")]
+        #[diagnostic(code(c8asm::parse::conditional::missing_condition))]
+        MissingCondition {
+            #[label("after this")]
+            after: SourceSpan,
+        },
+        #[error("Invalid type for condition")]
+        #[diagnostic(
+            code(c8asm::parse::conditional::condition_type),
+            help(
+                "`.if` expects a number or constant, optionally compared to another, `.ifdef` expects a constant name"
+            )
+        )]
+        ConditionType {
+            #[label("here")]
+            at: SourceSpan,
+        },
+        #[error("The value of this condition isn't known yet")]
+        #[diagnostic(
+            code(c8asm::parse::conditional::unknown_value),
+            help("constants used in `.if` must be defined above it")
+        )]
+        UnknownValue {
+            #[label("here")]
+            at: SourceSpan,
+        },
+        #[error("Conditional directives expect a single condition")]
+        #[diagnostic(code(c8asm::parse::conditional::too_many))]
+        TooMany {
+            #[label("here")]
+            at: SourceSpan,
+        },
+        #[error("Unknown comparison")]
+        #[diagnostic(
+            code(c8asm::parse::conditional::unknown_comparison),
+            help("try one of `==`, `!=`, `<`, `<=`, `>` or `>=`")
+        )]
+        UnknownComparison {
+            #[label("here")]
+            at: SourceSpan,
+        },
     }
 
     #[derive(Debug, Error, Diagnostic)]
     pub enum ConstantDefinitionError {
         #[error("Constants must be defined with a value")]
+        #[diagnostic(code(c8asm::parse::constant::constant_needs_value))]
         ConstantNeedsValue {
             #[label("here")]
             constant: SourceSpan,
         },
         #[error("Constant value must be numeric or constant")]
+        #[diagnostic(code(c8asm::parse::constant::constant_value_type))]
         ConstantValueType {
             #[label("here")]
             value: SourceSpan,
         },
+        #[error("Constant '{}' has the name of a register", .name)]
+        #[diagnostic(
+            code(c8asm::parse::constant::register_name),
+            help("`v0` to `vf` are reserved for registers, so pick another name")
+        )]
+        RegisterName {
+            name: String,
+            #[label("here")]
+            constant: SourceSpan,
+        },
     }
 
     #[derive(Debug, Error, Diagnostic)]
     pub enum DataDefinitionError {
         #[error("Invalid item in data list")]
+        #[diagnostic(code(c8asm::parse::data::data_entry_invalid_type))]
         DataEntryInvalidType {
             #[label("here")]
             item: SourceSpan,
@@ -631,12 +1141,16 @@ mod error {
             should_be: String,
         },
         #[error("Raw data cannot be included as-is")]
-        #[diagnostic(help("use `.data` to include arbitrary data"))]
+        #[diagnostic(
+            code(c8asm::parse::data::exposed_data),
+            help("use `.data` to include arbitrary data")
+        )]
         ExposedData {
             #[label("here")]
             at: SourceSpan,
         },
         #[error("This number is too big to be used as a piece of raw data")]
+        #[diagnostic(code(c8asm::parse::data::number_too_big))]
         NumberTooBig {
             number: u16,
             #[label("here")]
@@ -646,19 +1160,92 @@ mod error {
         },
     }
 
+    #[derive(Debug, Error, Diagnostic)]
+    pub enum IncludeBinaryError {
+        #[error("Including a binary file requires a path")]
+        #[diagnostic(
+            code(c8asm::parse::incbin::missing_path),
+            help("give the path as a string, such as `.incbin \"sprites.bin\"`")
+        )]
+        MissingPath {
+            #[label("after this")]
+            after: SourceSpan,
+        },
+        #[error("The path must be a string")]
+        #[diagnostic(
+            code(c8asm::parse::incbin::path_type),
+            help("put the path in double quotes")
+        )]
+        PathType {
+            #[label("here")]
+            at: SourceSpan,
+        },
+        #[error("`.incbin` expects a single path")]
+        #[diagnostic(code(c8asm::parse::incbin::too_many))]
+        TooMany {
+            #[label("here")]
+            at: SourceSpan,
+        },
+        #[error("Could not read '{}': {}", .path.display(), .reason)]
+        #[diagnostic(code(c8asm::parse::incbin::read))]
+        Read {
+            path: PathBuf,
+            reason: String,
+            #[label("included here")]
+            at: SourceSpan,
+        },
+        #[error("This file is {} bytes, more than fits in a ROM", .size)]
+        #[diagnostic(code(c8asm::parse::incbin::too_large))]
+        TooLarge {
+            size: u64,
+            #[help]
+            help: String,
+            #[label("included here")]
+            at: SourceSpan,
+        },
+    }
+
+    #[derive(Debug, Error, Diagnostic)]
+    pub enum EntryDefinitionError {
+        #[error("Setting the entry point requires a label")]
+        #[diagnostic(
+            code(c8asm::parse::entry::missing_label),
+            help("name the label to start at, such as `.entry main`")
+        )]
+        MissingLabel {
+            #[label("after this")]
+            after: SourceSpan,
+        },
+        #[error("The entry point must be a label")]
+        #[diagnostic(code(c8asm::parse::entry::label_type))]
+        LabelType {
+            #[label("here")]
+            at: SourceSpan,
+        },
+        #[error("`.entry` expects a single label")]
+        #[diagnostic(code(c8asm::parse::entry::too_many))]
+        TooMany {
+            #[label("here")]
+            at: SourceSpan,
+        },
+    }
+
     #[derive(Debug, Error, Diagnostic)]
     pub enum AssertDefinitionError {
         #[error("Asserting an address requires an address to assert")]
+        #[diagnostic(code(c8asm::parse::assert::missing_addr))]
         MissingAddr {
             #[label("here")]
             at: SourceSpan,
         },
         #[error("The address must be numeric")]
+        #[diagnostic(code(c8asm::parse::assert::addr_type))]
         AddrType {
             #[label("here")]
             at: SourceSpan,
         },
         #[error("Assert expects a single address")]
+        #[diagnostic(code(c8asm::parse::assert::addr_too_many))]
         AddrTooMany {
             #[label("here")]
             at: SourceSpan,
@@ -668,31 +1255,37 @@ mod error {
     #[derive(Debug, Error, Diagnostic)]
     pub enum NameDefinitionError {
         #[error("Invalid item in name list")]
+        #[diagnostic(code(c8asm::parse::name::name_invalid_type))]
         NameInvalidType {
             #[label("here")]
             at: SourceSpan,
         },
         #[error("Expected a comma")]
+        #[diagnostic(code(c8asm::parse::name::expected_comma))]
         ExpectedComma {
             #[label("here")]
             at: SourceSpan,
         },
         #[error("Expected an equals sign")]
+        #[diagnostic(code(c8asm::parse::name::missing_equals))]
         MissingEquals {
             #[label("after this")]
             after: SourceSpan,
         },
         #[error("Expected an equals sign")]
+        #[diagnostic(code(c8asm::parse::name::not_an_equals))]
         NotAnEquals {
             #[label("here")]
             at: SourceSpan,
         },
         #[error("Missing a value for assignment")]
+        #[diagnostic(code(c8asm::parse::name::missing_value))]
         MissingValue {
             #[label("after this")]
             after: SourceSpan,
         },
         #[error("Invalid type for assignment")]
+        #[diagnostic(code(c8asm::parse::name::invalid_value_type))]
         InvalidValueType {
             #[label("here")]
             at: SourceSpan,
@@ -713,6 +1306,164 @@ mod error {
         ConstantDefinitionError,
         DataDefinitionError,
         AssertDefinitionError,
-        NameDefinitionError
+        NameDefinitionError,
+        IncludeBinaryError,
+        EntryDefinitionError
     );
 }
+
+/// How many bytes each entry of a data directive takes up
+/// Words are big-endian by default, the same as instructions
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum DataWidth {
+    Byte,
+    BigEndianWord,
+    LittleEndianWord,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compilation::compile;
+    use crate::instruction_sets::Chip8InstructionSet;
+    use crate::tokenizing::tokenize;
+    use c8common::Address;
+
+    fn assemble(source: &str) -> Vec<u8> {
+        let rom =
+            compile::<Chip8InstructionSet>(parse(tokenize(source).unwrap()).unwrap()).unwrap();
+        (0..4).map(|offset| rom[Address::new(offset)].0).collect()
+    }
+
+    #[test]
+    fn test_ifdef_selects_block() {
+        let body = "
+            .ifdef $SCHIP
+            .data 0x00, 0xFF
+            .else
+            .data 0x00, 0xE0
+            .endif
+            .if $SPEED
+            .data 0x12, 0x34
+            .endif
+        ";
+        let with = assemble(&format!("$SCHIP 1\n$SPEED 0\n{}", body));
+        let without = assemble(&format!("$SPEED 2\n{}", body));
+
+        assert_eq!(with, [0x00, 0xFF, 0x00, 0x00]);
+        assert_eq!(without, [0x00, 0xE0, 0x12, 0x34]);
+    }
+
+    #[test]
+    fn test_if_comparisons() {
+        let body = "
+            .if $LEVEL == 1
+            .data 0x11
+            .else
+            .data 0x22
+            .endif
+            .if $LEVEL >= 2
+            .data 0x33
+            .endif
+            .if $LEVEL != $MAX
+            .data 0x44
+            .endif
+        ";
+        let at = |level| assemble(&format!("$MAX 3\n$LEVEL {}\n{}", level, body));
+
+        assert_eq!(at(1), [0x11, 0x44, 0x00, 0x00]);
+        assert_eq!(at(2), [0x22, 0x33, 0x44, 0x00]);
+        assert_eq!(at(3), [0x22, 0x33, 0x00, 0x00]);
+
+        let unknown = parse(tokenize("$A 1\n.if $A =< 2\n.endif\n").unwrap());
+        assert!(matches!(
+            unknown,
+            Err(ConversionError::Conditional(
+                ConditionalError::UnknownComparison { .. }
+            ))
+        ));
+        let missing = parse(tokenize("$A 1\n.if $A <\n.endif\n").unwrap());
+        assert!(matches!(
+            missing,
+            Err(ConversionError::Conditional(
+                ConditionalError::MissingCondition { .. }
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_unbalanced_conditionals() {
+        let unclosed = parse(tokenize(".ifdef $A\ncls\n").unwrap());
+        assert!(matches!(
+            unclosed,
+            Err(ConversionError::Conditional(
+                ConditionalError::Unclosed { .. }
+            ))
+        ));
+        let unmatched = parse(tokenize("cls\n.endif\n").unwrap());
+        assert!(matches!(
+            unmatched,
+            Err(ConversionError::Conditional(
+                ConditionalError::Unmatched { .. }
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_register_names_are_reserved() {
+        let label = parse(tokenize("cls\nvf:\njp vf\n").unwrap());
+        assert!(matches!(
+            label,
+            Err(ConversionError::RegisterLabel { name, .. }) if name == "vf"
+        ));
+        let constant = parse(tokenize("$V3 4\n").unwrap());
+        assert!(matches!(
+            constant,
+            Err(ConversionError::Invocation(InvocationError::Constant(
+                ConstantDefinitionError::RegisterName { .. }
+            )))
+        ));
+        assert!(parse(tokenize("a1:\njp a1\n").unwrap()).is_ok());
+    }
+
+    #[test]
+    fn test_word_data() {
+        assert_eq!(assemble(".dw 0x1234, 0xAB\n"), [0x12, 0x34, 0x00, 0xAB]);
+        assert_eq!(assemble(".dw_le 0x1234, 0xAB\n"), [0x34, 0x12, 0xAB, 0x00]);
+    }
+
+    #[test]
+    fn test_include_binary() {
+        let dir = std::env::temp_dir().join(format!("c8asm_incbin_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("sprite.bin"), [0xF0, 0x90, 0xF0]).unwrap();
+        std::fs::write(dir.join("huge.bin"), [0; 0x1000]).unwrap();
+        let source = "cls\n.incbin \"sprite.bin\"\n.data 0xAA\n";
+
+        let items = parse_relative_to(tokenize(source).unwrap(), &dir).unwrap();
+        let rom = compile::<Chip8InstructionSet>(items).unwrap();
+        let bytes = (0..6).map(|offset| rom[Address::new(offset)].0);
+        assert!(bytes.eq([0x00, 0xE0, 0xF0, 0x90, 0xF0, 0xAA]));
+
+        let error = |source: &str| parse_relative_to(tokenize(source).unwrap(), &dir).unwrap_err();
+        assert!(matches!(
+            error(".incbin \"huge.bin\"\n"),
+            ConversionError::Invocation(InvocationError::IncludeBinary(
+                IncludeBinaryError::TooLarge { size: 0x1000, .. }
+            ))
+        ));
+        assert!(matches!(
+            error(".incbin \"missing.bin\"\n"),
+            ConversionError::Invocation(InvocationError::IncludeBinary(
+                IncludeBinaryError::Read { .. }
+            ))
+        ));
+        assert!(matches!(
+            error(".incbin sprite\n"),
+            ConversionError::Invocation(InvocationError::IncludeBinary(
+                IncludeBinaryError::PathType { .. }
+            ))
+        ));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -37,6 +37,8 @@ pub enum Lexical {
     PrefixedIdent(Punct, String),
     Ident(String),
     Numeric(u16),
+    /// Double-quoted, with no escapes, as in `.incbin "sprites.bin"`
+    Str(String),
 }
 
 impl Lexical {
@@ -74,6 +76,13 @@ impl Lexical {
             _ => None,
         }
     }
+
+    pub fn to_str(self) -> Option<String> {
+        match self {
+            Self::Str(s) => Some(s),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
@@ -83,6 +92,28 @@ pub enum Punct {
     Colon,
     Dollar,
     Equals,
+    Less,
+    Greater,
+    Bang,
+    Minus,
+    Plus,
+}
+
+impl Punct {
+    pub fn symbol(self) -> char {
+        match self {
+            Self::Comma => ',',
+            Self::Period => '.',
+            Self::Colon => ':',
+            Self::Dollar => '$',
+            Self::Equals => '=',
+            Self::Less => '<',
+            Self::Greater => '>',
+            Self::Bang => '!',
+            Self::Minus => '-',
+            Self::Plus => '+',
+        }
+    }
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
@@ -92,9 +123,32 @@ pub struct Spanned<T> {
 }
 
 impl<T> Spanned<T> {
-    fn new(item: T, at: SourceSpan) -> Self {
+    /// Wraps an item with the source span it came from, so front-ends other than [`tokenize`]
+    /// can feed the parser
+    ///
+    /// ```
+    /// use c8asm::parsing::parse;
+    /// use c8asm::tokenizing::{Item, Lexical, Spanned};
+    ///
+    /// let tokens = vec![
+    ///     Spanned::new(Item::Lexical(Lexical::Ident("cls".to_string())), (0, 3).into()),
+    ///     Spanned::new(Item::Linebreak, (3, 1).into()),
+    /// ];
+    /// assert_eq!(tokens[0].span().offset(), 0);
+    /// assert_eq!(tokens[1].item(), &Item::Linebreak);
+    /// assert!(parse(tokens).is_ok());
+    /// ```
+    pub fn new(item: T, at: SourceSpan) -> Self {
         Self { item, at }
     }
+
+    pub fn item(&self) -> &T {
+        &self.item
+    }
+
+    pub fn span(&self) -> SourceSpan {
+        self.at
+    }
 }
 
 impl<T> From<Spanned<T>> for SourceSpan {
@@ -142,27 +196,30 @@ impl MultiCharItem {
         Self::Numeric(s.into())
     }
 
-    fn into_item(self) -> Result<Item, InvalidNumberReason> {
+    fn into_item(self, at: SourceSpan) -> Result<Item, TokenizingError> {
         Ok(match self {
             Self::PrefixedIdent(prefix, s) => match s {
                 Some(s) => Item::Lexical(Lexical::PrefixedIdent(prefix, s)),
-                None => Item::Punct(prefix),
+                None => Err(TokenizingError::missing_name(prefix, at))?,
             },
             Self::Ident(s) => Item::Lexical(Lexical::Ident(s)),
             Self::Numeric(n) => Item::Lexical(Lexical::Numeric({
-                let large = if let Some(hex) = n.strip_prefix("0x") {
+                let large = if let Some(hex) = n.strip_prefix("0x").or(n.strip_prefix("0X")) {
+                    if hex.is_empty() {
+                        Err(InvalidNumberReason::EmptyHex(n.clone()).error(at))?
+                    }
                     u64::from_str_radix(hex, 16)
-                        .map_err(|_| InvalidNumberReason::InvalidHex(n.clone()))?
+                        .map_err(|_| InvalidNumberReason::InvalidHex(n.clone()).error(at))?
                 } else if let Some(hex) = n.strip_prefix("0b") {
                     u64::from_str_radix(hex, 2)
-                        .map_err(|_| InvalidNumberReason::InvalidBinary(n.clone()))?
+                        .map_err(|_| InvalidNumberReason::InvalidBinary(n.clone()).error(at))?
                 } else {
                     n.parse()
-                        .map_err(|_| InvalidNumberReason::InvalidDecimal(n.clone()))?
+                        .map_err(|_| InvalidNumberReason::InvalidDecimal(n.clone()).error(at))?
                 };
                 large
                     .try_into()
-                    .map_err(|_| InvalidNumberReason::TooLarge(n))?
+                    .map_err(|_| InvalidNumberReason::TooLarge(n).error(at))?
             })),
         })
     }
@@ -181,10 +238,17 @@ pub fn tokenize(original: &str) -> Result<Vec<Spanned<Item>>, TokenizingError> {
     let mut output = Vec::new();
 
     let mut current_ident: Option<(usize, MultiCharItem)> = None;
+    let mut current_string: Option<(usize, String)> = None;
     let mut go_to_next_linebreak = false;
+    let mut after_carriage_return = false;
     for (index, character) in original.chars().enumerate() {
+        // `\r\n` is a single line ending, and the `\r` has already been handled
+        if std::mem::replace(&mut after_carriage_return, character == '\r') && character == '\n' {
+            continue;
+        }
+
         if go_to_next_linebreak {
-            if character == '\n' {
+            if is_line_ending(character) {
                 go_to_next_linebreak = false;
             }
             continue;
@@ -198,12 +262,45 @@ pub fn tokenize(original: &str) -> Result<Vec<Spanned<Item>>, TokenizingError> {
             });
         }
 
+        if let Some((start, ref mut string)) = current_string {
+            if character == '"' {
+                let range = (start, index + 1 - start).into();
+                output.push(Spanned::new(
+                    Item::Lexical(Lexical::Str(std::mem::take(string))),
+                    range,
+                ));
+                current_string = None;
+            } else if is_line_ending(character) {
+                return Err(UnterminatedString {
+                    at: (start, index - start).into(),
+                });
+            } else {
+                string.push(character);
+            }
+            continue;
+        }
+
+        if character == '"' {
+            if let Some((start, current)) = current_ident {
+                let range = (start, index - start).into();
+                output.push(Spanned::new(current.into_item(range)?, range));
+                current_ident = None;
+            }
+            current_string = Some((index, String::new()));
+            continue;
+        }
+
         {
             let punctuation = match character {
                 ':' => Some(Item::Punct(Punct::Colon)),
                 ';' => Some(Item::Linebreak),
                 ',' => Some(Item::Punct(Punct::Comma)),
                 '=' => Some(Item::Punct(Punct::Equals)),
+                '<' => Some(Item::Punct(Punct::Less)),
+                '>' => Some(Item::Punct(Punct::Greater)),
+                '!' => Some(Item::Punct(Punct::Bang)),
+                '-' => Some(Item::Punct(Punct::Minus)),
+                '+' => Some(Item::Punct(Punct::Plus)),
                 '$' => Some(Item::Punct(Punct::Dollar)),
                 '.' => Some(Item::Punct(Punct::Period)),
                 _ => None,
@@ -212,10 +309,7 @@ pub fn tokenize(original: &str) -> Result<Vec<Spanned<Item>>, TokenizingError> {
             if let Some(punctuation) = punctuation {
                 if let Some((start, current)) = current_ident {
                     let range = (start, index - start).into();
-                    output.push(Spanned::new(
-                        current.into_item().map_err(|e| e.error(range))?,
-                        range,
-                    ));
+                    output.push(Spanned::new(current.into_item(range)?, range));
                     current_ident = None;
                 }
 
@@ -237,6 +331,8 @@ pub fn tokenize(original: &str) -> Result<Vec<Spanned<Item>>, TokenizingError> {
                     });
                 }
 
+                // A label ends its line, so whatever follows on the same line (more labels, data
+                // or an instruction) is parsed as if it were on the next, at the same address
                 if punctuation == Item::Punct(Punct::Colon) {
                     output.push(Spanned {
                         item: Item::Linebreak,
@@ -250,13 +346,10 @@ pub fn tokenize(original: &str) -> Result<Vec<Spanned<Item>>, TokenizingError> {
         if character.is_whitespace() {
             if let Some((start, current)) = current_ident {
                 let range = (start, index - start).into();
-                output.push(Spanned::new(
-                    current.into_item().map_err(|e| e.error(range))?,
-                    range,
-                ));
+                output.push(Spanned::new(current.into_item(range)?, range));
                 current_ident = None;
             }
-            if character == '\n' {
+            if is_line_ending(character) {
                 output.push(Spanned::new(Item::Linebreak, (index, 1).into()))
             }
             continue;
@@ -272,6 +365,10 @@ pub fn tokenize(original: &str) -> Result<Vec<Spanned<Item>>, TokenizingError> {
         }
 
         if character.is_ascii_digit() {
+            // A name after `.` or `$` can't start with a digit, as `.5` reads like a number
+            if let Some((start, MultiCharItem::PrefixedIdent(prefix, None))) = current_ident {
+                return Err(TokenizingError::missing_name(prefix, (start, 1).into()));
+            }
             if let Some((_, ref mut current)) = current_ident {
                 current.push(character);
             } else {
@@ -286,28 +383,36 @@ pub fn tokenize(original: &str) -> Result<Vec<Spanned<Item>>, TokenizingError> {
         });
     }
 
+    if let Some((start, _)) = current_string {
+        return Err(UnterminatedString {
+            at: (start, original.len() - start).into(),
+        });
+    }
+
     if let Some((start, current)) = current_ident {
         let range = (start, original.len() - start).into();
-        output.push(Spanned::new(
-            current.into_item().map_err(|e| e.error(range))?,
-            range,
-        ));
+        output.push(Spanned::new(current.into_item(range)?, range));
     }
 
     Ok(output)
 }
 
+/// Both `\n` and a bare `\r` end a line
+fn is_line_ending(character: char) -> bool {
+    character == '\n' || character == '\r'
+}
+
 #[derive(Debug, Error, Diagnostic)]
 pub enum TokenizingError {
     #[error("Unrecognised item '{}'", .offending_character)]
-    #[diagnostic(code(c8common::asm::unrecognised_item))]
+    #[diagnostic(code(c8asm::tokenize::unrecognised_item))]
     UnrecognisedItem {
         offending_character: char,
         #[label("here")]
         at: SourceSpan,
     },
     #[error("Invalid item '{}'", .offending_string)]
-    #[diagnostic(code(c8common::asm::invalid_item))]
+    #[diagnostic(code(c8asm::tokenize::invalid_item))]
     InvalidNumber {
         offending_string: String,
         #[help]
@@ -315,8 +420,27 @@ pub enum TokenizingError {
         #[label("here")]
         at: SourceSpan,
     },
+    #[error("Expected a name after '{}'", .prefix)]
+    #[diagnostic(
+        code(c8asm::tokenize::missing_name),
+        help("names after `.` and `$` start with a letter or `_`")
+    )]
+    MissingName {
+        prefix: char,
+        #[label("here")]
+        at: SourceSpan,
+    },
+    #[error("This string is never closed")]
+    #[diagnostic(
+        code(c8asm::tokenize::unterminated_string),
+        help("strings end with a `\"` on the same line")
+    )]
+    UnterminatedString {
+        #[label("starting here")]
+        at: SourceSpan,
+    },
     #[error("Non-ASCII Unicode is not supported")]
-    #[diagnostic(code(c8common::asm::unicode))]
+    #[diagnostic(code(c8asm::tokenize::unicode))]
     Unicode {
         offending_character: char,
         #[label("here")]
@@ -324,10 +448,47 @@ pub enum TokenizingError {
     },
 }
 
+impl TokenizingError {
+    pub fn at(&self) -> SourceSpan {
+        match self {
+            Self::UnrecognisedItem { at, .. }
+            | Self::InvalidNumber { at, .. }
+            | Self::MissingName { at, .. }
+            | Self::UnterminatedString { at }
+            | Self::Unicode { at, .. } => *at,
+        }
+    }
+
+    fn missing_name(prefix: Punct, at: SourceSpan) -> Self {
+        Self::MissingName {
+            prefix: prefix.symbol(),
+            at,
+        }
+    }
+
+    /// The 1-based line and column of the start of the error within `source`
+    pub fn line_col(&self, source: &str) -> (usize, usize) {
+        let mut position = (1, 1);
+        let mut previous = None;
+        for character in source.chars().take(self.at().offset()) {
+            if character == '\n' && previous == Some('\r') {
+                // Already counted by the `\r`
+            } else if is_line_ending(character) {
+                position = (position.0 + 1, 1);
+            } else {
+                position.1 += 1;
+            }
+            previous = Some(character);
+        }
+        position
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum InvalidNumberReason {
     TooLarge(String),
     InvalidHex(String),
+    EmptyHex(String),
     InvalidBinary(String),
     InvalidDecimal(String),
 }
@@ -335,8 +496,11 @@ pub enum InvalidNumberReason {
 impl InvalidNumberReason {
     pub fn error(self, at: SourceSpan) -> TokenizingError {
         let (reason, offending) = match self {
-            InvalidNumberReason::TooLarge(s) => ("this number is too large", s),
+            InvalidNumberReason::TooLarge(s) => {
+                ("numbers must fit in a u16, at most 0xFFFF (65535)", s)
+            }
             InvalidNumberReason::InvalidHex(s) => ("the hexadecimal is invalid", s),
+            InvalidNumberReason::EmptyHex(s) => ("hex literal has no digits", s),
             InvalidNumberReason::InvalidBinary(s) => ("invalid binary", s),
             InvalidNumberReason::InvalidDecimal(s) => ("invalid decimal", s),
         };
@@ -347,3 +511,105 @@ impl InvalidNumberReason {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_line_col() {
+        let source = "cls\nret\n  ld v0, #\n";
+        let error = tokenize(source).unwrap_err();
+        assert!(matches!(error, TokenizingError::UnrecognisedItem { .. }));
+        assert_eq!(error.line_col(source), (3, 10));
+
+        let crlf = source.replace('\n', "\r\n");
+        assert_eq!(tokenize(&crlf).unwrap_err().line_col(&crlf), (3, 10));
+    }
+
+    #[test]
+    fn test_line_endings() {
+        let source = "; header comment\nstart:\n  ld v0, 0x10 ; trailing\n\n\tjp start\n";
+        let items = |source: &str| {
+            tokenize(source)
+                .unwrap()
+                .into_iter()
+                .map(|spanned| spanned.item)
+                .collect::<Vec<_>>()
+        };
+
+        let lf = items(source);
+        assert_eq!(
+            lf.iter().filter(|item| **item == Item::Linebreak).count(),
+            6
+        );
+        assert_eq!(items(&source.replace('\n', "\r\n")), lf);
+        assert_eq!(items(&source.replace('\n', "\r")), lf);
+    }
+
+    #[test]
+    fn test_prefix_without_name() {
+        let missing = |source: &str| match tokenize(source).unwrap_err() {
+            TokenizingError::MissingName { prefix, at } => (prefix, at.offset(), at.len()),
+            other => panic!("expected a missing name, got {:?}", other),
+        };
+        assert_eq!(missing("ld v0, $\n"), ('$', 7, 1));
+        assert_eq!(missing("ld v0, $"), ('$', 7, 1));
+        assert_eq!(missing(".5\n"), ('.', 0, 1));
+        assert_eq!(missing("ld v0, ., v1\n"), ('.', 7, 1));
+        assert!(tokenize(".data 1, 2\n$_x 1\n").is_ok());
+    }
+
+    #[test]
+    fn test_strings() {
+        let items = tokenize(".incbin \"a b;c.bin\"\n").unwrap();
+        assert_eq!(
+            items[1],
+            Spanned::new(
+                Item::Lexical(Lexical::Str("a b;c.bin".to_string())),
+                (8, 11).into()
+            )
+        );
+        assert_eq!(items[2].item, Item::Linebreak);
+        for source in [".incbin \"a.bin\n", ".incbin \"a.bin"] {
+            assert!(matches!(
+                tokenize(source),
+                Err(TokenizingError::UnterminatedString { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_hex_prefix() {
+        let number = |source: &str| match tokenize(source).unwrap().remove(0).item {
+            Item::Lexical(Lexical::Numeric(n)) => n,
+            other => panic!("expected a number, got {:?}", other),
+        };
+        let reason = |source: &str| match tokenize(source).unwrap_err() {
+            TokenizingError::InvalidNumber { reason, .. } => reason.unwrap(),
+            other => panic!("expected an invalid number, got {:?}", other),
+        };
+        assert_eq!(number("0XFF"), 0xFF);
+        assert_eq!(number("0xff"), 0xFF);
+        assert_eq!(reason("0x"), "hex literal has no digits");
+        assert_eq!(reason("0X\n"), "hex literal has no digits");
+        assert_eq!(reason("0xG"), "the hexadecimal is invalid");
+    }
+
+    #[test]
+    fn test_u16_limit() {
+        let result = tokenize("ld v0, 0x10000\n");
+        match result {
+            Err(TokenizingError::InvalidNumber {
+                offending_string,
+                reason,
+                ..
+            }) => {
+                assert_eq!(offending_string, "0x10000");
+                assert!(reason.unwrap().contains("0xFFFF"));
+            }
+            other => panic!("expected an invalid number, got {:?}", other),
+        }
+        assert!(tokenize("ld i, 0xFFFF\n").is_ok());
+    }
+}
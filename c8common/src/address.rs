@@ -14,7 +14,17 @@ impl Address {
         Self(at)
     }
 
+    /// Each argument must be a nibble, which is checked in debug builds
+    /// In release builds the extra bits are ignored, so the result is always a valid address
     pub fn from_triplet(high: u8, mid: u8, low: u8) -> Self {
+        debug_assert!(
+            high <= 0xF && mid <= 0xF && low <= 0xF,
+            "Invalid nibbles for address {:X} {:X} {:X}",
+            high,
+            mid,
+            low
+        );
+        let (high, mid, low) = (high & 0xF, mid & 0xF, low & 0xF);
         let inner = ((high as u16) << 8) | ((mid as u16) << 4) | low as u16;
         Self(inner)
     }
@@ -59,6 +69,39 @@ impl From<Address> for usize {
     }
 }
 
+/// Frontends and `chip8_base` address memory with a plain `u16`
+impl From<Address> for u16 {
+    fn from(value: Address) -> Self {
+        value.0
+    }
+}
+
+impl TryFrom<u16> for Address {
+    type Error = AddressOutOfRange;
+
+    fn try_from(value: u16) -> Result<Self, Self::Error> {
+        if value <= Self::MAX.0 {
+            Ok(Self(value))
+        } else {
+            Err(AddressOutOfRange(value))
+        }
+    }
+}
+
+impl TryFrom<usize> for Address {
+    type Error = AddressOutOfRange;
+
+    fn try_from(value: usize) -> Result<Self, Self::Error> {
+        u16::try_from(value)
+            .map_err(|_| AddressOutOfRange(u16::MAX))?
+            .try_into()
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Error)]
+#[error("Address 0x{0:04X} is outside of the 12-bit address space")]
+pub struct AddressOutOfRange(pub u16);
+
 impl Shr<usize> for Address {
     type Output = Self;
 
@@ -95,6 +138,7 @@ use crate::data::impl_fmt;
 use crate::{Datum, Nibble};
 use std::cmp::Ordering;
 use std::ops::{BitAnd, Shr};
+use thiserror::Error;
 impl_fmt! {
     (Address, u16),
     std::fmt::LowerHex,
@@ -102,3 +146,36 @@ impl_fmt! {
     std::fmt::Octal,
     std::fmt::Binary
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_raw_round_trip() {
+        for raw in [0x000_u16, 0x200, 0xFFF] {
+            let addr = Address::try_from(raw).unwrap();
+            assert_eq!(u16::from(addr), raw);
+            assert_eq!(Address::try_from(usize::from(addr)), Ok(addr));
+        }
+        assert_eq!(
+            Address::try_from(0x1000_u16),
+            Err(AddressOutOfRange(0x1000))
+        );
+        assert!(Address::try_from(0x10000_usize).is_err());
+    }
+
+    #[test]
+    fn test_from_triplet() {
+        assert_eq!(Address::from_triplet(0x2, 0x0, 0x0), Address::PROGRAM_START);
+        assert_eq!(Address::from_triplet(0xA, 0xB, 0xC), Address::new(0xABC));
+        assert_eq!(Address::from_triplet(0xF, 0xF, 0xF), Address::MAX);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "Invalid nibbles")]
+    fn test_from_triplet_rejects_wide_nibbles() {
+        Address::from_triplet(0x1, 0x10, 0x0);
+    }
+}
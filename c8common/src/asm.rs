@@ -1,24 +1,51 @@
-use crate::memory::Memory;
-use crate::{Address, Datum, NUMBER_OF_ADDRESSES};
+use crate::memory::{diff_data, Memory};
+use crate::{Address, Datum, Instruction, RawInstruction, NUMBER_OF_ADDRESSES};
+use flate2::read::GzDecoder;
 use log::info;
 use std::cmp::Ordering;
+use std::io::{Read, Write};
 use std::ops::Index;
 use std::path::Path;
 use tap::prelude::*;
 
 #[derive(Debug, Clone)]
 #[allow(missing_copy_implementations)]
-pub struct ROM([Datum; NUMBER_OF_ADDRESSES - Address::PROGRAM_START_INDEX]);
+pub struct ROM(
+    [Datum; NUMBER_OF_ADDRESSES - Address::PROGRAM_START_INDEX],
+    /// Bytes of actual program, before any zero padding
+    usize,
+);
 
 impl ROM {
     pub fn new() -> Self {
-        Self([Datum(0); NUMBER_OF_ADDRESSES - Address::PROGRAM_START_INDEX])
+        Self(
+            [Datum(0); NUMBER_OF_ADDRESSES - Address::PROGRAM_START_INDEX],
+            0,
+        )
     }
 
+    /// Without knowing where the program ends, trailing zeroes are taken to be padding
     pub fn containing(
         containing: [Datum; NUMBER_OF_ADDRESSES - Address::PROGRAM_START_INDEX],
     ) -> Self {
-        Self(containing)
+        let len = containing
+            .iter()
+            .rposition(|datum| datum.0 != 0)
+            .map_or(0, |i| i + 1);
+        Self(containing, len)
+    }
+
+    /// For when the program is known to end after `len` bytes, even if it ends in zeroes
+    pub fn containing_program(
+        containing: [Datum; NUMBER_OF_ADDRESSES - Address::PROGRAM_START_INDEX],
+        len: usize,
+    ) -> Self {
+        Self(containing, len)
+    }
+
+    /// How many bytes of the ROM are program, rather than padding
+    pub fn program_len(&self) -> usize {
+        self.1
     }
 
     pub fn save(&self, path: impl AsRef<Path>) -> Result<(), std::io::Error> {
@@ -26,17 +53,23 @@ impl ROM {
         std::fs::write(path, buf)
     }
 
+    /// The same bytes as `save`, to somewhere other than a file, such as stdout
+    pub fn write_to(&self, mut to: impl Write) -> Result<(), std::io::Error> {
+        to.write_all(&self.0.map(|datum| datum.0))
+    }
+
     pub fn from_bytes(mut bytes: Vec<u8>) -> Result<Self, LoadError> {
+        let len = bytes.len();
         if bytes.len() < NUMBER_OF_ADDRESSES - Address::PROGRAM_START_INDEX {
             info!(
                 "Padding bytes from {} to {}",
                 bytes.len(),
                 NUMBER_OF_ADDRESSES - Address::PROGRAM_START_INDEX
             );
-            bytes.extend(
-                std::iter::repeat(0)
-                    .take(NUMBER_OF_ADDRESSES - Address::PROGRAM_START_INDEX - bytes.len()),
-            )
+            bytes.extend(std::iter::repeat_n(
+                0,
+                NUMBER_OF_ADDRESSES - Address::PROGRAM_START_INDEX - bytes.len(),
+            ))
         }
         match bytes
             .len()
@@ -48,7 +81,7 @@ impl ROM {
                     .try_conv::<[u8; NUMBER_OF_ADDRESSES - Address::PROGRAM_START_INDEX]>()
                     .unwrap();
                 let data = bytes.map(Datum);
-                Ok(Self(data))
+                Ok(Self(data, len))
             }
             Ordering::Greater => Err(LoadError::WrongSize {
                 size: bytes.len(),
@@ -57,11 +90,93 @@ impl ROM {
         }
     }
 
+    /// Gzipped files are decompressed first
     pub fn from_file(path: impl AsRef<Path>) -> Result<Self, FileLoadError> {
         let file_contents = std::fs::read(path).map_err(FileLoadError::IO)?;
+        let file_contents =
+            Self::decompress_if_gzipped(file_contents).map_err(FileLoadError::IO)?;
         Self::from_bytes(file_contents).map_err(FileLoadError::LoadError)
     }
 
+    fn decompress_if_gzipped(bytes: Vec<u8>) -> Result<Vec<u8>, std::io::Error> {
+        const GZIP_MAGIC: [u8; 2] = [0x1F, 0x8B];
+        if !bytes.starts_with(&GZIP_MAGIC) {
+            return Ok(bytes);
+        }
+        info!("Decompressing gzipped ROM");
+        let mut decompressed = vec![];
+        GzDecoder::new(&bytes[..]).read_to_end(&mut decompressed)?;
+        Ok(decompressed)
+    }
+
+    /// Packs the encoded instructions one after another, starting at `PROGRAM_START`
+    pub fn from_instructions(instructions: &[Instruction]) -> Result<Self, LoadError> {
+        let bytes = instructions
+            .iter()
+            .flat_map(|instruction| instruction.to_data().to_bytes())
+            .collect();
+        Self::from_bytes(bytes)
+    }
+
+    /// One line per 16 bytes, each starting with the address of its first byte
+    /// Only the `program_len` bytes of program are dumped, zeroes included, not the padding
+    pub fn to_hex_dump(&self) -> String {
+        self.0[..self.program_len()]
+            .chunks(16)
+            .enumerate()
+            .map(|(line, chunk)| {
+                let bytes = chunk
+                    .iter()
+                    .map(|datum| format!("{:02X}", datum.0))
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                format!(
+                    "{:03X}: {}\n",
+                    Address::PROGRAM_START_INDEX + line * 16,
+                    bytes
+                )
+            })
+            .collect()
+    }
+
+    /// Decodes the program two bytes at a time, at absolute addresses from `PROGRAM_START`
+    /// Stops at the end of the program rather than walking the padding after it
+    pub fn instructions(
+        &self,
+    ) -> impl Iterator<Item = (Address, Result<Instruction, RawInstruction>)> + '_ {
+        self.0[..self.program_len().next_multiple_of(2)]
+            .chunks_exact(2)
+            .enumerate()
+            .map(|(i, pair)| {
+                let raw = RawInstruction::from((pair[0], pair[1]));
+                let address = Address::new((Address::PROGRAM_START_INDEX + i * 2) as u16);
+                (address, Instruction::try_from_data(raw).map_err(|_| raw))
+            })
+    }
+
+    /// Like `Memory::diff`, with addresses relative to `PROGRAM_START` as when indexing
+    pub fn diff(&self, other: &Self) -> Vec<(Address, Datum, Datum)> {
+        diff_data(&self.0, &other.0)
+    }
+
+    /// CRC-32 (as used by zip and PNG) of the program bytes, ignoring any padding after them
+    pub fn crc32(&self) -> u32 {
+        crc32fast::hash(&self.program_bytes())
+    }
+
+    /// SHA-1 of the program bytes, ignoring any padding after them, to identify the ROM against
+    /// a database of known programs
+    pub fn sha1(&self) -> [u8; 20] {
+        sha1_smol::Sha1::from(self.program_bytes()).digest().bytes()
+    }
+
+    fn program_bytes(&self) -> Vec<u8> {
+        self.0[..self.program_len()]
+            .iter()
+            .map(|datum| datum.0)
+            .collect()
+    }
+
     pub(crate) fn into_data(self) -> [Datum; NUMBER_OF_ADDRESSES - 0x200] {
         self.0
     }
@@ -101,3 +216,141 @@ impl Index<Address> for ROM {
 //         &mut self.0[index.try_conv::<usize>().unwrap()]
 //     }
 // }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::GeneralRegister;
+
+    #[test]
+    fn test_from_instructions() {
+        let instructions = [
+            Instruction::ClearScreen,
+            Instruction::LoadRegByte(GeneralRegister::V3, 0x42),
+            Instruction::Jump(Address::new(0x204)),
+        ];
+        let rom = ROM::from_instructions(&instructions).unwrap();
+
+        let disassembled = (0..instructions.len() as u16)
+            .map(|i| {
+                let raw =
+                    RawInstruction::from((rom[Address::new(i * 2)], rom[Address::new(i * 2 + 1)]));
+                Instruction::try_from_data(raw).unwrap()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(&instructions[..], &disassembled[..]);
+        assert_eq!(rom[Address::new(6)], Datum(0));
+    }
+
+    #[test]
+    fn test_instructions() {
+        let instructions = [
+            Instruction::ClearScreen,
+            Instruction::LoadRegByte(GeneralRegister::V3, 0x42),
+            Instruction::Jump(Address::new(0x204)),
+        ];
+        // Followed by an invalid instruction, and half of one more
+        let bytes = vec![0x00, 0xE0, 0x63, 0x42, 0x12, 0x04, 0x51, 0x21, 0x00];
+        let rom = ROM::from_bytes(bytes).unwrap();
+
+        let decoded = rom.instructions().collect::<Vec<_>>();
+        assert_eq!(
+            decoded,
+            [
+                (Address::new(0x200), Ok(instructions[0])),
+                (Address::new(0x202), Ok(instructions[1])),
+                (Address::new(0x204), Ok(instructions[2])),
+                (Address::new(0x206), Err(RawInstruction::from(0x5121))),
+                (Address::new(0x208), Ok(Instruction::Nop)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_gzipped_file() {
+        use flate2::write::GzEncoder;
+        use flate2::Compression;
+        use std::io::Write;
+
+        let program = [0x00, 0xE0, 0x12, 0x02];
+        let mut encoder = GzEncoder::new(vec![], Compression::default());
+        encoder.write_all(&program).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let dir = std::env::temp_dir().join(format!("c8common_gzip_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let (plain, gzipped) = (dir.join("plain.ch8"), dir.join("rom.ch8.gz"));
+        std::fs::write(&plain, program).unwrap();
+        std::fs::write(&gzipped, compressed).unwrap();
+        let (plain, gzipped) = (
+            ROM::from_file(plain).unwrap(),
+            ROM::from_file(gzipped).unwrap(),
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(gzipped.to_hex_dump(), "200: 00 E0 12 02\n");
+        assert!(plain.diff(&gzipped).is_empty());
+    }
+
+    #[test]
+    fn test_hashes() {
+        let rom = ROM::from_bytes(b"abc".to_vec()).unwrap();
+        assert_eq!(rom.crc32(), 0x3524_41C2);
+        assert_eq!(
+            rom.sha1(),
+            [
+                0xA9, 0x99, 0x3E, 0x36, 0x47, 0x06, 0x81, 0x6A, 0xBA, 0x3E, 0x25, 0x71, 0x78, 0x50,
+                0xC2, 0x6C, 0x9C, 0xD0, 0xD8, 0x9D
+            ]
+        );
+        // Two blocks once padded
+        let long = ROM::from_bytes(vec![0x12; 100]).unwrap();
+        assert_eq!(long.crc32(), crc32fast::hash(&[0x12; 100]));
+        assert_eq!(
+            long.sha1(),
+            [
+                0xEE, 0xF1, 0x3D, 0x6B, 0xE9, 0xB9, 0xF9, 0xB5, 0xC4, 0x35, 0x28, 0xB7, 0x39, 0x2D,
+                0x94, 0xA8, 0x20, 0xFB, 0x82, 0xFB
+            ]
+        );
+    }
+
+    #[test]
+    fn test_hex_dump() {
+        let mut bytes = (0..18).collect::<Vec<u8>>();
+        bytes.extend([0; 2]);
+        let dump = ROM::from_bytes(bytes).unwrap().to_hex_dump();
+
+        let mut lines = dump.lines();
+        assert_eq!(
+            lines.next(),
+            Some("200: 00 01 02 03 04 05 06 07 08 09 0A 0B 0C 0D 0E 0F")
+        );
+        assert_eq!(lines.next(), Some("210: 10 11 00 00"));
+        assert_eq!(lines.next(), None);
+        assert_eq!(ROM::new().to_hex_dump(), "");
+    }
+
+    #[test]
+    fn test_diff() {
+        let rom = ROM::from_instructions(&[Instruction::ClearScreen, Instruction::Return]).unwrap();
+        let other = ROM::from_instructions(&[Instruction::ClearScreen, Instruction::Nop]).unwrap();
+        assert_eq!(
+            rom.diff(&other),
+            vec![(Address::new(3), Datum(0xEE), Datum(0x00)),]
+        );
+    }
+
+    #[test]
+    fn test_from_instructions_too_large() {
+        let instructions = vec![
+            Instruction::ClearScreen;
+            (NUMBER_OF_ADDRESSES - Address::PROGRAM_START_INDEX) / 2 + 1
+        ];
+        assert!(matches!(
+            ROM::from_instructions(&instructions),
+            Err(LoadError::WrongSize { .. })
+        ));
+    }
+}
@@ -1,13 +1,27 @@
 use crate::control::execute::Interpreter;
 use crate::key::Keys;
 use crate::memory::Memory;
-use crate::{Address, Datum, Display, GeneralRegister};
+use crate::{Address, Datum, Display, GeneralRegister, Instruction};
 
+pub mod cycles;
 pub mod execute;
 
 pub trait ControlledInterpreter {
     fn step(&mut self, keys: Keys, frame: &mut FrameInfo);
 
+    /// Like `step`, but passes the decoded instruction through `on_decode` before executing it,
+    /// which can replace it or return `None` to skip it.
+    /// Interpreters that don't override this ignore `on_decode`.
+    fn step_with_decode(
+        &mut self,
+        keys: Keys,
+        frame: &mut FrameInfo,
+        on_decode: &mut dyn FnMut(Instruction) -> Option<Instruction>,
+    ) {
+        let _ = on_decode;
+        self.step(keys, frame)
+    }
+
     fn display(&self) -> &Display;
 
     fn delay_timer_register(&self) -> &Datum;
@@ -16,6 +30,16 @@ pub trait ControlledInterpreter {
     fn sound_timer_register(&self) -> &Datum;
     fn sound_timer_register_mut(&mut self) -> &mut Datum;
 
+    /// Primes the delay timer from outside a program, e.g. in a test harness
+    fn set_delay_timer(&mut self, to: Datum) {
+        *self.delay_timer_register_mut() = to;
+    }
+
+    /// Primes the sound timer from outside a program, e.g. in a test harness
+    fn set_sound_timer(&mut self, to: Datum) {
+        *self.sound_timer_register_mut() = to;
+    }
+
     fn timer_tick_60hz(&mut self) -> TimerTick {
         let mut tick = TimerTick::new();
         tick.delay(self.delay_timer_register_mut().towards_zero());
@@ -61,6 +85,16 @@ pub trait ControlledInterpreter {
         self.stack_mut().pop().expect("Stack underflow!")
     }
 
+    /// How many calls deep the program is
+    fn stack_depth(&self) -> usize {
+        self.stack().len()
+    }
+
+    /// The address the current subroutine will return to, if there is one
+    fn stack_top(&self) -> Option<Address> {
+        self.stack().last().copied()
+    }
+
     fn memory(&self) -> &Memory;
     fn memory_mut(&mut self) -> &mut Memory;
 
@@ -81,6 +115,11 @@ pub struct FrameInfo {
     pub(crate) screen_modified: bool,
     pub(crate) buzzer_change_state: Option<bool>,
     pub(crate) wait_for_key: Option<GeneralRegister>,
+    pub(crate) halted: bool,
+    pub(crate) wait_for_vblank: bool,
+    pub(crate) executed: Option<Instruction>,
+    /// Bit `n` is set if `Vn` was changed this step
+    pub(crate) changed_registers: u16,
 }
 
 impl FrameInfo {
@@ -90,6 +129,10 @@ impl FrameInfo {
             screen_modified: false,
             buzzer_change_state: None,
             wait_for_key: None,
+            halted: false,
+            wait_for_vblank: false,
+            executed: None,
+            changed_registers: 0,
         }
     }
 
@@ -97,10 +140,6 @@ impl FrameInfo {
         self.screen_modified = true;
     }
 
-    fn set_buzzer(&mut self, to: bool) {
-        self.buzzer_change_state = Some(to);
-    }
-
     pub fn busywait(&mut self) {
         self.entered_busywait = true;
     }
@@ -108,6 +147,29 @@ impl FrameInfo {
     pub fn wait_for_key_on(&mut self, register: GeneralRegister) {
         self.wait_for_key = Some(register);
     }
+
+    /// Stops the interpreter permanently, e.g. after running into uninitialised memory
+    pub fn halt(&mut self) {
+        self.halted = true;
+    }
+
+    /// Stalls the next instruction until after the next 60Hz timer tick
+    pub fn wait_for_vblank(&mut self) {
+        self.wait_for_vblank = true;
+    }
+
+    pub fn set_executed(&mut self, instruction: Instruction) {
+        self.executed = Some(instruction);
+    }
+
+    pub fn mark_register_changed(&mut self, register: GeneralRegister) {
+        self.changed_registers |= 1 << register.index();
+    }
+
+    /// The instruction executed during this step, if the interpreter reported one
+    pub fn executed(&self) -> Option<Instruction> {
+        self.executed
+    }
 }
 
 pub trait ControlledToInterpreter: ControlledInterpreter {
@@ -163,4 +225,5 @@ pub enum InterpreterState {
     Held,
     WaitForKey(GeneralRegister),
     BusyWaiting,
+    Halted,
 }
@@ -0,0 +1,19 @@
+use crate::Instruction;
+use std::fmt::Debug;
+
+/// How many steps each instruction takes to execute
+/// An instruction costing more than one cycle stalls the interpreter for the remaining steps,
+/// while the timers keep running
+pub trait CycleModel: Debug + Send {
+    fn cost(&self, instruction: Instruction) -> u32;
+}
+
+/// Every instruction takes exactly one step
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct UniformCycles;
+
+impl CycleModel for UniformCycles {
+    fn cost(&self, _: Instruction) -> u32 {
+        1
+    }
+}
@@ -1,12 +1,60 @@
+use crate::control::cycles::{CycleModel, UniformCycles};
 use crate::control::{ControlledInterpreter, ControlledToInterpreter, FrameInfo, InterpreterState};
+use crate::hooks::disabled::EnabledHook;
 use crate::hooks::{FurtherHooks, InterpreterHook};
 use crate::key::Keys;
-use crate::Display;
+use crate::{Address, Display, GeneralRegister, Instruction};
 use getset::{Getters, MutGetters};
 use log::{debug, info, trace, warn};
 use std::marker::PhantomData;
+use std::sync::mpsc::Sender;
 use std::time::Duration;
 
+/// Rate of the delay and sound timers, independent of how many instructions are run per second
+pub const TIMER_FREQUENCY: u32 = 60;
+/// The fractions of a tick the timer clock counts in, so step rates that don't divide 60 evenly
+/// stay in integer maths
+const TICK_RESOLUTION: u64 = 1_000_000_000;
+
+/// Where the 60Hz timers get their time from
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum TimerSource {
+    /// Each step is `1 / step_frequency` seconds of emulated time, scaled by any simulated
+    /// frequency
+    Steps,
+    /// Only [`Interpreter::advance_timers`] moves the timers, so a frontend can drive them from a
+    /// real clock however many instructions it runs
+    External,
+}
+
+/// Counts time towards the next 60Hz timer tick, kept apart from the instruction rate
+#[derive(Debug, Copy, Clone, Default)]
+struct TimerClock {
+    /// In `1 / TICK_RESOLUTION`ths of a tick
+    progress: u64,
+}
+
+impl TimerClock {
+    /// Moves the clock on by `amount` fractions of a tick, returning how many ticks completed
+    fn advance(&mut self, amount: u64) -> u64 {
+        self.progress = self.progress.saturating_add(amount);
+        let ticks = self.progress / TICK_RESOLUTION;
+        self.progress %= TICK_RESOLUTION;
+        ticks
+    }
+
+    fn until_tick(&self) -> u64 {
+        TICK_RESOLUTION - self.progress
+    }
+
+    /// `elapsed` in fractions of a tick, which with a resolution of 10^9 is 60 per nanosecond
+    fn amount_for(elapsed: Duration) -> u64 {
+        let amount =
+            elapsed.as_nanos() * (TIMER_FREQUENCY as u64 * TICK_RESOLUTION) as u128 / 1_000_000_000;
+        amount.min(u64::MAX as u128) as u64
+    }
+}
+
 #[derive(Debug, Getters, MutGetters)]
 #[getset(get = "pub", get_mut = "pub")]
 pub struct Interpreter<I: ControlledInterpreter> {
@@ -14,21 +62,96 @@ pub struct Interpreter<I: ControlledInterpreter> {
     buzzer_active: bool,
     step_frequency: u32,
     internal_frequency_scale: Option<f32>,
-    sixty_hertz_progress: Duration,
+    #[getset(skip)]
+    timer_clock: TimerClock,
+    timer_source: TimerSource,
     state: InterpreterState,
+    /// What a hook held the interpreter from, such as waiting for a key, to go back to on resume
+    #[getset(skip)]
+    held_from: Option<InterpreterState>,
+    #[getset(skip)]
+    cycle_model: Box<dyn CycleModel>,
+    /// Steps left before the previous instruction has finished executing
+    stalled_cycles: u32,
+    /// The keys after hooks had mapped them, in the last step
     #[getset(skip)]
-    hooks: Vec<Box<dyn InterpreterHook<I>>>,
+    last_keys: Keys,
+    #[getset(skip)]
+    hooks: Vec<EnabledHook<Box<dyn InterpreterHook<I>>>>,
+    #[getset(skip)]
+    event_sender: Option<Sender<InterpreterEvent>>,
+}
+
+/// Something observable that happened during a step, for frontends that would rather not poll
+#[derive(Debug, Clone)]
+pub enum InterpreterEvent {
+    ScreenUpdated(Box<Display>),
+    BuzzerChanged(bool),
+    WaitingForKey(GeneralRegister),
+    BusyWaiting,
+    /// A hook, such as a breakpoint, has held the interpreter
+    Held,
+    Halted,
+    Resumed,
+}
+
+/// Why `Interpreter::run_until` returned
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum StopReason {
+    /// The predicate held
+    Reached,
+    /// The cycle cap was hit first
+    CycleLimit,
+    /// The interpreter can't continue without outside input
+    Stuck(InterpreterState),
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct RunUntil {
+    pub reason: StopReason,
+    /// The number of steps taken
+    pub cycles: u32,
+}
+
+impl InterpreterEvent {
+    fn entered(state: InterpreterState) -> Self {
+        match state {
+            InterpreterState::Normal => Self::Resumed,
+            InterpreterState::Held => Self::Held,
+            InterpreterState::WaitForKey(reg) => Self::WaitingForKey(reg),
+            InterpreterState::BusyWaiting => Self::BusyWaiting,
+            InterpreterState::Halted => Self::Halted,
+        }
+    }
 }
 
 impl<T: ControlledInterpreter> Interpreter<T> {
     pub fn step(&mut self, keys: Keys) -> Option<Display> {
+        if self.event_sender.is_none() {
+            return self.step_inner(keys);
+        }
+
+        let (state, buzzer) = (self.state, self.buzzer_active);
+        let display = self.step_inner(keys);
+        if let Some(display) = display {
+            self.send_event(InterpreterEvent::ScreenUpdated(Box::new(display)));
+        }
+        if self.buzzer_active != buzzer {
+            self.send_event(InterpreterEvent::BuzzerChanged(self.buzzer_active));
+        }
+        if self.state != state {
+            self.send_event(InterpreterEvent::entered(self.state));
+        }
+        display
+    }
+
+    fn step_inner(&mut self, keys: Keys) -> Option<Display> {
         self.hook_pre_cycle();
         let keys = self.hook_map_keys(self.state, keys);
+        self.hook_key_change(keys);
         match self.state {
             InterpreterState::Normal => {}
-            InterpreterState::Held => {
-                todo!("Check for resume")
-            }
+            InterpreterState::Held => return None,
             InterpreterState::WaitForKey(reg) => {
                 if keys.pressed() {
                     let parsed_keys = keys.one_key();
@@ -44,34 +167,58 @@ impl<T: ControlledInterpreter> Interpreter<T> {
                     return None;
                 }
             }
-            InterpreterState::BusyWaiting => return None,
+            InterpreterState::BusyWaiting | InterpreterState::Halted => return None,
         }
         trace!("Beginning step.");
         let mut frame_info = FrameInfo::empty();
 
         // TODO: Hook for RTC registers
-        let internal_frequency = self.internal_frequency_scale.unwrap_or(1.);
-        let more_progress =
-            Duration::from_secs_f32(self.speed().as_secs_f32() * internal_frequency);
-        self.sixty_hertz_progress += more_progress;
-        while self.sixty_hertz_progress.as_secs_f32() >= 1. / 60. {
-            self.sixty_hertz_progress -= Duration::from_secs_f32(1. / 60.);
-            if self.inner.timer_tick_60hz().buzzer_active() {
-                frame_info.set_buzzer(true);
-            } else {
-                frame_info.set_buzzer(false);
+        let timer_step = self.timer_step();
+        if self.timer_source == TimerSource::Steps {
+            for _ in 0..self.timer_clock.advance(timer_step) {
+                self.inner.timer_tick_60hz();
+            }
+        }
+        self.sync_buzzer(&mut frame_info);
+        if self.stalled_cycles > 0 {
+            trace!("Stalled, {} cycles left.", self.stalled_cycles);
+            self.stalled_cycles -= 1;
+        } else {
+            self.hook_before_step(&mut frame_info);
+            let hooks = &mut self.hooks;
+            self.inner
+                .step_with_decode(keys, &mut frame_info, &mut |instruction| {
+                    hooks
+                        .iter_mut()
+                        .try_fold(instruction, |instruction, hook| hook.on_decode(instruction))
+                });
+            // The program may have set or cleared the sound timer itself
+            self.sync_buzzer(&mut frame_info);
+            trace!("Step complete!");
+            self.hook_after_step(&mut frame_info);
+            if let Some(Instruction::Sys(address)) = frame_info.executed() {
+                self.hook_machine_call(address);
+            }
+            if let Some(executed) = frame_info.executed() {
+                self.stalled_cycles = self.cycle_model.cost(executed).saturating_sub(1);
+            }
+            if frame_info.wait_for_vblank && timer_step > 0 {
+                let steps_to_tick = self.timer_clock.until_tick().div_ceil(timer_step);
+                self.stalled_cycles = self
+                    .stalled_cycles
+                    .max(steps_to_tick.saturating_sub(1) as u32);
             }
         }
-        self.hook_before_step(&mut frame_info);
-        self.inner.step(keys, &mut frame_info);
-        trace!("Step complete!");
-        self.hook_after_step(&mut frame_info);
 
         let FrameInfo {
             screen_modified,
             buzzer_change_state,
             entered_busywait,
             wait_for_key,
+            halted,
+            wait_for_vblank: _,
+            executed: _,
+            changed_registers: _,
         } = frame_info;
 
         if let Some(reg) = wait_for_key {
@@ -83,6 +230,10 @@ impl<T: ControlledInterpreter> Interpreter<T> {
             self.state = InterpreterState::BusyWaiting;
         }
 
+        if halted {
+            self.state = InterpreterState::Halted;
+        }
+
         if let Some(buzzer) = buzzer_change_state {
             self.buzzer_active = buzzer;
         }
@@ -95,6 +246,109 @@ impl<T: ControlledInterpreter> Interpreter<T> {
         None
     }
 
+    /// Steps with no keys pressed until `predicate` holds, checking before every step
+    pub fn run_until(
+        &mut self,
+        max_cycles: u32,
+        mut predicate: impl FnMut(&T) -> bool,
+    ) -> RunUntil {
+        let mut cycles = 0;
+        let reason = loop {
+            if predicate(&self.inner) {
+                break StopReason::Reached;
+            }
+            if self.state != InterpreterState::Normal {
+                break StopReason::Stuck(self.state);
+            }
+            if cycles == max_cycles {
+                break StopReason::CycleLimit;
+            }
+            self.step(Keys::from_raw([false; 16]));
+            cycles += 1;
+        };
+        RunUntil { reason, cycles }
+    }
+
+    /// Continues execution after a hook has held the interpreter, back in whatever state it was
+    /// held from, so a pending `Fx0A` still waits for its key
+    pub fn resume(&mut self) {
+        if self.state == InterpreterState::Held {
+            info!("Resuming execution.");
+            self.state = self.held_from.take().unwrap_or(InterpreterState::Normal);
+            self.send_event(InterpreterEvent::entered(self.state));
+        }
+    }
+
+    /// Runs the 60Hz timers for `duration` without executing any instructions, so a program
+    /// waiting on the delay timer gets there without spinning through the wait
+    pub fn fast_forward_timers(&mut self, duration: Duration) {
+        let ticks = duration.as_nanos() * TIMER_FREQUENCY as u128 / 1_000_000_000;
+        self.run_timer_ticks(ticks.min(u64::MAX as u128) as u64);
+    }
+
+    /// Moves the timers on by `elapsed`, keeping any part of a tick for next time. This is how
+    /// timers advance with [`TimerSource::External`].
+    pub fn advance_timers(&mut self, elapsed: Duration) {
+        let ticks = self.timer_clock.advance(TimerClock::amount_for(elapsed));
+        self.run_timer_ticks(ticks);
+    }
+
+    fn run_timer_ticks(&mut self, ticks: u64) {
+        // Both timers are a byte, so they're at zero after this many ticks
+        let ticks = ticks.min(u8::MAX as u64 + 1);
+        let buzzer = self.buzzer_active;
+        for _ in 0..ticks {
+            self.inner.timer_tick_60hz();
+        }
+        self.buzzer_active = self.sound_timer_running();
+        if self.buzzer_active != buzzer {
+            self.send_event(InterpreterEvent::BuzzerChanged(self.buzzer_active));
+        }
+    }
+
+    /// How far each step moves the timer clock, in fractions of a tick
+    fn timer_step(&self) -> u64 {
+        let scale = self.internal_frequency_scale.unwrap_or(1.) as f64;
+        (TIMER_FREQUENCY as f64 * scale * TICK_RESOLUTION as f64 / self.step_frequency as f64)
+            .round() as u64
+    }
+
+    fn sound_timer_running(&self) -> bool {
+        self.inner.sound_timer_register().0 > 0
+    }
+
+    /// The buzzer sounds exactly while the sound timer is above zero. Only a change from the
+    /// last step is recorded, so a timer that is cleared and set again within one step doesn't
+    /// toggle the buzzer.
+    fn sync_buzzer(&self, frame_info: &mut FrameInfo) {
+        let active = self.sound_timer_running();
+        frame_info.buzzer_change_state = (active != self.buzzer_active).then_some(active);
+    }
+
+    fn send_event(&self, event: InterpreterEvent) {
+        if let Some(sender) = &self.event_sender {
+            // The receiving end going away shouldn't stop the interpreter
+            let _ = sender.send(event);
+        }
+    }
+
+    pub fn hook_count(&self) -> usize {
+        self.hooks.len()
+    }
+
+    /// The hooks in the order they were added, each of which may be switched off
+    pub fn hooks(&self) -> impl Iterator<Item = &EnabledHook<Box<dyn InterpreterHook<T>>>> {
+        self.hooks.iter()
+    }
+
+    /// The `index`th hook added, to enable or disable it
+    pub fn hook_mut(
+        &mut self,
+        index: usize,
+    ) -> Option<&mut EnabledHook<Box<dyn InterpreterHook<T>>>> {
+        self.hooks.get_mut(index)
+    }
+
     pub fn speed(&self) -> Duration {
         Duration::from_secs_f32(1. / (self.step_frequency as f32))
     }
@@ -107,9 +361,15 @@ impl<T: ControlledToInterpreter> Interpreter<T> {
             buzzer_active: false,
             step_frequency: 8,
             internal_frequency_scale: None,
-            sixty_hertz_progress: Duration::ZERO,
+            timer_clock: TimerClock::default(),
+            timer_source: TimerSource::Steps,
             state: InterpreterState::Normal,
+            held_from: None,
+            cycle_model: Box::new(UniformCycles),
+            stalled_cycles: 0,
+            last_keys: Keys::from_raw([false; 16]),
             hooks: vec![],
+            event_sender: None,
         }
     }
 }
@@ -121,6 +381,19 @@ impl<T: ControlledToInterpreter> Interpreter<T> {
         }
     }
 
+    fn hook_key_change(&mut self, keys: Keys) {
+        let (pressed, released) = (
+            keys.pressed_since(self.last_keys),
+            self.last_keys.pressed_since(keys),
+        );
+        self.last_keys = keys;
+        if pressed.pressed() || released.pressed() {
+            for hook in &mut self.hooks {
+                hook.on_key_change(pressed, released);
+            }
+        }
+    }
+
     fn hook_before_step(&mut self, frame: &mut FrameInfo) {
         for hook in &mut self.hooks {
             hook.before_step(&mut self.inner, frame);
@@ -133,10 +406,20 @@ impl<T: ControlledToInterpreter> Interpreter<T> {
         }
     }
 
+    fn hook_machine_call(&mut self, address: Address) {
+        for hook in &mut self.hooks {
+            hook.machine_call(&mut self.inner, address);
+        }
+    }
+
     fn hook_post_cycle(&mut self) {
+        let before = self.state;
         for hook in &mut self.hooks {
             hook.post_cycle(&mut self.state);
         }
+        if self.state == InterpreterState::Held && before != InterpreterState::Held {
+            self.held_from = Some(before);
+        }
     }
 
     fn hook_map_keys(&mut self, state: InterpreterState, mut keys: Keys) -> Keys {
@@ -156,13 +439,22 @@ impl<T: ControlledToInterpreter> Interpreter<T> {
 impl<T: ControlledToInterpreter> Interpreter<T> {
     fn new_with_hooks(from: T, hooks: Vec<Box<dyn InterpreterHook<T>>>) -> Self {
         Self {
-            hooks,
+            hooks: hooks.into_iter().map(EnabledHook::new).collect(),
             ..Interpreter::new(from)
         }
     }
 
+    /// Steps per second, which must be at least 1; 0 is taken as 1
     pub fn with_frequency(mut self, frequency: u32) -> Self {
-        self.step_frequency = frequency;
+        if frequency == 0 {
+            warn!("A frequency of 0 would never run anything, using 1 instead");
+        }
+        self.step_frequency = frequency.max(1);
+        self
+    }
+
+    pub fn with_timer_source(mut self, source: TimerSource) -> Self {
+        self.timer_source = source;
         self
     }
 
@@ -170,6 +462,16 @@ impl<T: ControlledToInterpreter> Interpreter<T> {
         self.internal_frequency_scale = frequency_scale;
         self
     }
+
+    pub fn with_event_sender(mut self, sender: Sender<InterpreterEvent>) -> Self {
+        self.event_sender = Some(sender);
+        self
+    }
+
+    pub fn with_cycle_model(mut self, model: impl CycleModel + 'static) -> Self {
+        self.cycle_model = Box::new(model);
+        self
+    }
 }
 
 impl<T: ControlledInterpreter> Interpreter<T> {
@@ -24,14 +24,44 @@ impl Datum {
     pub fn inner(self) -> u8 {
         self.0
     }
+
+    /// The wrapped sum, and whether it carried out of the byte
+    pub fn overflowing_add(self, rhs: Datum) -> (Datum, bool) {
+        let (sum, carry) = self.0.overflowing_add(rhs.0);
+        (Self(sum), carry)
+    }
+
+    pub fn wrapping_add(self, rhs: Datum) -> Datum {
+        Self(self.0.wrapping_add(rhs.0))
+    }
+
+    pub fn wrapping_sub(self, rhs: Datum) -> Datum {
+        Self(self.0.wrapping_sub(rhs.0))
+    }
+}
+
+/// Frontends and `chip8_base` work with plain bytes
+impl From<u8> for Datum {
+    fn from(value: u8) -> Self {
+        Self(value)
+    }
+}
+
+impl From<Datum> for u8 {
+    fn from(value: Datum) -> Self {
+        value.0
+    }
 }
 
 impl Nibble {
     pub fn new_from_half_byte(byte: u8) -> Self {
-        if byte & 0xF0 != 0 {
-            panic!("Invalid value for nibble {}", byte);
-        }
-        Self(byte)
+        Self::try_from_half_byte(byte)
+            .unwrap_or_else(|| panic!("Invalid value for nibble {}", byte))
+    }
+
+    /// `None` if `byte` has any of its upper four bits set
+    pub fn try_from_half_byte(byte: u8) -> Option<Self> {
+        (byte & 0xF0 == 0).then_some(Self(byte))
     }
 
     pub fn as_half_byte(&self) -> u8 {
@@ -106,3 +136,27 @@ impl_fmt!(
     std::fmt::Octal,
     std::fmt::Binary
 );
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_raw_round_trip() {
+        for raw in [0_u8, 0x7F, 0xFF] {
+            assert_eq!(u8::from(Datum::from(raw)), raw);
+        }
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        assert_eq!(Datum(0xF0).overflowing_add(Datum(0x10)), (Datum(0), true));
+        assert_eq!(
+            Datum(0xF0).overflowing_add(Datum(0x0F)),
+            (Datum(0xFF), false)
+        );
+        assert_eq!(Datum(0xFF).wrapping_add(Datum(2)), Datum(1));
+        assert_eq!(Datum(1).wrapping_sub(Datum(2)), Datum(0xFF));
+        assert_eq!(Datum(5).wrapping_sub(Datum(5)), Datum(0));
+    }
+}
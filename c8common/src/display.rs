@@ -1,61 +1,474 @@
 use crate::pixel::Pixel;
 use crate::Datum;
+use log::warn;
 
+/// The CHIP-8 screen, and SUPER-CHIP's low resolution mode
+pub const LORES_WIDTH: usize = 64;
+pub const LORES_HEIGHT: usize = 32;
+/// The SUPER-CHIP high resolution screen, twice as large each way
+pub const HIRES_WIDTH: usize = 128;
+pub const HIRES_HEIGHT: usize = 64;
+
+/// Big enough for hires. In lowres only the top-left `LORES_WIDTH` by `LORES_HEIGHT` is used.
+type Plane = [[Pixel; HIRES_WIDTH]; HIRES_HEIGHT];
+
+const BLANK_PLANE: Plane = [[Pixel::Black; HIRES_WIDTH]; HIRES_HEIGHT];
+
+/// The screen, as two XO-CHIP bit planes. Plain CHIP-8 programs only ever select the first, which
+/// is the one `raw`, `get_pixel` and the other single-plane accessors read and write.
+/// Everything is in pixels of the current resolution, see [`Display::set_hires`].
+/// Equality ignores the dirty region.
 #[derive(Debug, Copy, Clone)]
-pub struct Display([[Pixel; 64]; 32]);
+pub struct Display {
+    planes: [Plane; 2],
+    selected: u8,
+    hires: bool,
+    dirty: Option<DirtyRect>,
+}
+
+/// The part of the screen changed since a frontend last redrew, see [`Display::take_dirty`]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct DirtyRect {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl DirtyRect {
+    /// The whole lowres screen
+    pub const FULL: Self = Self {
+        x: 0,
+        y: 0,
+        width: LORES_WIDTH,
+        height: LORES_HEIGHT,
+    };
+
+    /// The whole hires screen
+    pub const FULL_HIRES: Self = Self {
+        x: 0,
+        y: 0,
+        width: HIRES_WIDTH,
+        height: HIRES_HEIGHT,
+    };
+
+    fn pixel(x: usize, y: usize) -> Self {
+        Self {
+            x,
+            y,
+            width: 1,
+            height: 1,
+        }
+    }
+
+    /// The smallest rectangle covering both
+    pub fn union(self, other: Self) -> Self {
+        let (x, y) = (self.x.min(other.x), self.y.min(other.y));
+        let right = (self.x + self.width).max(other.x + other.width);
+        let bottom = (self.y + self.height).max(other.y + other.height);
+        Self {
+            x,
+            y,
+            width: right - x,
+            height: bottom - y,
+        }
+    }
+}
 
 impl Display {
     pub fn blank() -> Self {
-        Self([[Pixel::Black; 64]; 32])
+        Self::from([[Pixel::Black; 64]; 32])
+    }
+
+    /// The first plane, one slice per row, at the current resolution
+    pub fn raw(&self) -> Vec<&[Pixel]> {
+        self.active(&self.planes[0])
+    }
+
+    /// The same row-major layout as `chip8_base::Display`, which only has the lowres grid, so in
+    /// hires this is just the top-left quarter
+    pub fn into_raw(self) -> [[Pixel; 64]; 32] {
+        std::array::from_fn(|y| std::array::from_fn(|x| self.planes[0][y][x]))
+    }
+
+    /// For black-on-white presentation; collisions still use the real pixels
+    pub fn raw_inverted(&self) -> Vec<Vec<Pixel>> {
+        self.raw()
+            .into_iter()
+            .map(|row| row.iter().map(|pixel| pixel.inverted()).collect())
+            .collect()
+    }
+
+    /// The number of columns in the grid at the current resolution, which frontends should use
+    /// rather than assuming 64
+    pub fn width(&self) -> usize {
+        if self.hires {
+            HIRES_WIDTH
+        } else {
+            LORES_WIDTH
+        }
+    }
+
+    /// The number of rows in the grid, see [`Display::width`]
+    pub fn height(&self) -> usize {
+        if self.hires {
+            HIRES_HEIGHT
+        } else {
+            LORES_HEIGHT
+        }
+    }
+
+    pub fn is_hires(&self) -> bool {
+        self.hires
+    }
+
+    /// `00FF` to switch to hires or `00FE` to switch back to lowres. As in Octo, this clears every
+    /// plane, so nothing drawn at one resolution is reinterpreted at the other.
+    pub fn set_hires(&mut self, hires: bool) {
+        self.hires = hires;
+        self.planes = [BLANK_PLANE; 2];
+        self.mark_dirty(self.whole());
+    }
+
+    /// Plane `0` or `1` at the current resolution, or `None` for any other index
+    pub fn plane(&self, index: usize) -> Option<Vec<&[Pixel]>> {
+        self.planes.get(index).map(|plane| self.active(plane))
+    }
+
+    fn active<'a>(&self, plane: &'a Plane) -> Vec<&'a [Pixel]> {
+        plane[..self.height()]
+            .iter()
+            .map(|row| &row[..self.width()])
+            .collect()
+    }
+
+    fn whole(&self) -> DirtyRect {
+        if self.hires {
+            DirtyRect::FULL_HIRES
+        } else {
+            DirtyRect::FULL
+        }
+    }
+
+    /// The planes sprites are drawn into, as the `Fn01` bit mask: bit 0 is the first plane and bit
+    /// 1 the second
+    pub fn selected_planes(&self) -> u8 {
+        self.selected
+    }
+
+    /// `Fn01`, only the lowest two bits of `mask` are used. Selecting no planes makes sprites draw
+    /// nothing.
+    pub fn select_planes(&mut self, mask: u8) {
+        self.selected = mask & 0b11;
     }
 
-    pub fn raw(&self) -> &[[Pixel; 64]; 32] {
-        &self.0
+    /// How many bytes a sprite `rows` high takes with the current selection, as each selected plane
+    /// reads its own rows one after another
+    pub fn sprite_len(&self, rows: usize) -> usize {
+        rows * self.selected.count_ones() as usize
     }
 
+    /// The 2-bit colour of the pixel at column `x` and row `y`: bit 0 from the first plane and bit
+    /// 1 from the second. `None` if that is off the screen.
+    pub fn color_index(&self, x: usize, y: usize) -> Option<u8> {
+        if x >= self.width() || y >= self.height() {
+            return None;
+        }
+        let bit = |plane: &Plane| bool::from(plane[y][x]) as u8;
+        Some(bit(&self.planes[0]) | bit(&self.planes[1]) << 1)
+    }
+
+    /// [`Display::color_index`] for every pixel, in row-major order
+    pub fn to_color_indices(&self) -> Vec<Vec<u8>> {
+        (0..self.height())
+            .map(|y| {
+                (0..self.width())
+                    .map(|x| self.color_index(x, y).expect("in bounds"))
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// One line per row, with `#` for lit pixels and `.` for the rest
+    pub fn to_ascii(&self) -> String {
+        self.raw()
+            .iter()
+            .map(|row| {
+                let mut line = row
+                    .iter()
+                    .map(|pixel| if bool::from(*pixel) { '#' } else { '.' })
+                    .collect::<String>();
+                line.push('\n');
+                line
+            })
+            .collect()
+    }
+
+    /// Whether each pixel is lit, row by row, for frontends that draw their own cells
+    pub fn to_cells(&self) -> Vec<Vec<bool>> {
+        self.raw()
+            .iter()
+            .map(|row| row.iter().map(|pixel| bool::from(*pixel)).collect())
+            .collect()
+    }
+
+    /// Like [`Display::to_ascii`], but packs two rows into each line using half-block
+    /// characters, so the lowres screen fits in 16 lines of a terminal
+    pub fn to_half_blocks(&self) -> String {
+        self.raw()
+            .chunks(2)
+            .map(|rows| {
+                let mut line = rows[0]
+                    .iter()
+                    .zip(rows[1])
+                    .map(
+                        |(top, bottom)| match (bool::from(*top), bool::from(*bottom)) {
+                            (false, false) => ' ',
+                            (true, false) => '\u{2580}',
+                            (false, true) => '\u{2584}',
+                            (true, true) => '\u{2588}',
+                        },
+                    )
+                    .collect::<String>();
+                line.push('\n');
+                line
+            })
+            .collect()
+    }
+
+    /// The pixel at column `x` and row `y`, or `None` if that is off the screen
+    pub fn get_pixel(&self, x: usize, y: usize) -> Option<Pixel> {
+        self.raw().get(y)?.get(x).copied()
+    }
+
+    /// Sets the pixel at column `x` and row `y`, returning what was there.
+    /// Does nothing and returns `None` if that is off the screen.
+    pub fn set_pixel(&mut self, x: usize, y: usize, to: Pixel) -> Option<Pixel> {
+        self.get_pixel(x, y)?;
+        Some(self.set_pixel_at(0, x, y, to))
+    }
+
+    /// 64-bit FNV-1a over the pixels at the current resolution, one byte each in row-major order,
+    /// so it is the same on every platform and can be checked into tests.
+    /// The second plane is only hashed, after the first, once something has been drawn into it,
+    /// so plain CHIP-8 frames hash as they always have.
+    pub fn frame_hash(&self) -> u64 {
+        const OFFSET_BASIS: u64 = 0xCBF2_9CE4_8422_2325;
+        const PRIME: u64 = 0x0000_0100_0000_01B3;
+        let lit = |plane: &Plane| self.active(plane).concat().into_iter();
+        let second = lit(&self.planes[1]).any(bool::from);
+        let planes = if second {
+            &self.planes[..]
+        } else {
+            &self.planes[..1]
+        };
+        planes
+            .iter()
+            .flat_map(lit)
+            .fold(OFFSET_BASIS, |hash, pixel| {
+                (hash ^ bool::from(pixel) as u64).wrapping_mul(PRIME)
+            })
+    }
+
+    /// Whether the active pixel region of both displays is the same. A lowres frame compares
+    /// equal to its 2x upscale in hires.
+    pub fn pixels_eq(&self, other: &Self) -> bool {
+        (0..2).all(|plane| {
+            (0..HIRES_HEIGHT).all(|y| {
+                (0..HIRES_WIDTH).all(|x| self.upscaled(plane, x, y) == other.upscaled(plane, x, y))
+            })
+        })
+    }
+
+    /// The pixel covering hires column `x` and row `y`, whichever resolution this is in
+    fn upscaled(&self, plane: usize, x: usize, y: usize) -> Pixel {
+        if self.hires {
+            self.planes[plane][y][x]
+        } else {
+            self.planes[plane][y / 2][x / 2]
+        }
+    }
+
+    /// Blanks the selected planes, as XO-CHIP's `00E0` does, leaving any other alone
     pub fn clear(&mut self) {
-        self.0 = [[Pixel::Black; 64]; 32];
+        for plane in self.selected_mut() {
+            *plane = BLANK_PLANE;
+        }
+        self.mark_dirty(self.whole());
+    }
+
+    fn selected_mut(&mut self) -> impl Iterator<Item = &mut Plane> {
+        let selected = self.selected;
+        self.planes
+            .iter_mut()
+            .enumerate()
+            .filter(move |(index, _)| selected & (1 << index) != 0)
+            .map(|(_, plane)| plane)
+    }
+
+    /// Everything changed since the last call, or `None` if nothing has, so a frontend only needs
+    /// to redraw that part. Pixels set back to what they were still count.
+    pub fn take_dirty(&mut self) -> Option<DirtyRect> {
+        self.dirty.take()
+    }
+
+    fn mark_dirty(&mut self, rect: DirtyRect) {
+        self.dirty = Some(self.dirty.map_or(rect, |dirty| dirty.union(rect)));
+    }
+
+    /// Scroll amounts are in SUPER-CHIP hires pixels, as in `00CN`/`00FB`/`00FC`. In hires they
+    /// move that far, while in lowres (like SUPER-CHIP 1.1) every scroll moves half as far: `00FB`
+    /// moves 2 pixels, and `00CN` moves `N / 2` rows, rounding down. Like clearing, only the
+    /// selected planes move. The interpreter doesn't decode these instructions yet.
+    pub fn scroll_down(&mut self, hires_rows: u8) {
+        let (width, height) = (self.width(), self.height());
+        let rows = self.scaled(hires_rows as usize).min(height);
+        for plane in self.selected_mut() {
+            plane[..height].rotate_right(rows);
+            for row in &mut plane[..rows] {
+                row[..width].fill(Pixel::Black);
+            }
+        }
+        self.mark_dirty(self.whole());
+    }
+
+    /// `00FB`, see [`Display::scroll_down`]
+    pub fn scroll_right(&mut self) {
+        let (width, height) = (self.width(), self.height());
+        let columns = self.scaled(4);
+        for plane in self.selected_mut() {
+            for row in &mut plane[..height] {
+                row[..width].rotate_right(columns);
+                row[..columns].fill(Pixel::Black);
+            }
+        }
+        self.mark_dirty(self.whole());
+    }
+
+    /// `00FC`, see [`Display::scroll_down`]
+    pub fn scroll_left(&mut self) {
+        let (width, height) = (self.width(), self.height());
+        let columns = self.scaled(4);
+        for plane in self.selected_mut() {
+            for row in &mut plane[..height] {
+                row[..width].rotate_left(columns);
+                row[width - columns..width].fill(Pixel::Black);
+            }
+        }
+        self.mark_dirty(self.whole());
+    }
+
+    /// A distance in hires pixels as pixels of the current resolution
+    fn scaled(&self, distance: usize) -> usize {
+        if self.hires {
+            distance
+        } else {
+            distance / 2
+        }
     }
 
     pub fn sprite(&mut self, x: Datum, y: Datum, data: &[Datum]) -> ScreenModification {
+        self.sprite_with_clipping(x, y, data, false)
+    }
+
+    /// `sprite` for plain bytes, so rendering can be tested without going through memory
+    pub fn draw_sprite_bytes(&mut self, x: u8, y: u8, data: &[u8]) -> ScreenModification {
+        let data = data.iter().copied().map(Datum).collect::<Vec<_>>();
+        self.sprite(Datum(x), Datum(y), &data)
+    }
+
+    /// When clipping, the sprite's origin still wraps, but any part past the edge is not drawn.
+    /// With several planes selected, `data` is split evenly between them, first plane first, see
+    /// [`Display::sprite_len`]; a collision in any of them counts. Bytes left over from an uneven
+    /// split are not drawn, with a warning.
+    pub fn sprite_with_clipping(
+        &mut self,
+        x: Datum,
+        y: Datum,
+        data: &[Datum],
+        clip: bool,
+    ) -> ScreenModification {
+        let (x, y) = if clip {
+            (x.0 as usize % self.width(), y.0 as usize % self.height())
+        } else {
+            (x.0 as usize, y.0 as usize)
+        };
         let mut modified = ScreenModification::Nothing;
-        for (row, byte) in data.iter().enumerate().map(|(i, d)| (i + y.0 as usize, d)) {
+        let selected = (0..2)
+            .filter(|plane| self.selected & (1 << plane) != 0)
+            .collect::<Vec<_>>();
+        if selected.is_empty() {
+            return modified;
+        }
+        let rows = data.len() / selected.len();
+        let leftover = data.len() % selected.len();
+        if leftover != 0 {
+            warn!(
+                "Sprite of {} bytes can't be split between {} planes, ignoring the last {}",
+                data.len(),
+                selected.len(),
+                leftover
+            );
+        }
+        if rows == 0 {
+            return modified;
+        }
+        for (plane, data) in selected.into_iter().zip(data.chunks(rows)) {
+            self.draw_on_plane(plane, x, y, data, clip, &mut modified);
+        }
+        modified
+    }
+
+    fn draw_on_plane(
+        &mut self,
+        plane: usize,
+        x: usize,
+        y: usize,
+        data: &[Datum],
+        clip: bool,
+        modified: &mut ScreenModification,
+    ) {
+        let (width, height) = (self.width(), self.height());
+        for (row, byte) in data.iter().enumerate().map(|(i, d)| (i + y, d)) {
             for (column, bit) in Self::split_datum(*byte)
                 .into_iter()
                 .enumerate()
-                .map(|(i, b)| (i + x.0 as usize, b))
+                .map(|(i, b)| (i + x, b))
             {
+                if clip && (column >= width || row >= height) {
+                    continue;
+                }
                 if bit {
                     modified.set();
-                    if self.xor_pixel_at(column % 64, row % 32) {
+                    if self.xor_pixel_at(plane, column % width, row % height) {
                         modified.clear();
                     }
                 }
             }
         }
-        modified
     }
 
-    fn pixel_at(&self, x: usize, y: usize) -> &Pixel {
-        &self.0[y][x]
+    fn pixel_at(&self, plane: usize, x: usize, y: usize) -> &Pixel {
+        &self.planes[plane][y][x]
     }
 
-    fn pixel_at_mut(&mut self, x: usize, y: usize) -> &mut Pixel {
-        &mut self.0[y][x]
+    fn pixel_at_mut(&mut self, plane: usize, x: usize, y: usize) -> &mut Pixel {
+        &mut self.planes[plane][y][x]
     }
 
-    fn set_pixel_at(&mut self, x: usize, y: usize, to: Pixel) -> Pixel {
-        let old = *self.pixel_at(x, y);
-        *self.pixel_at_mut(x, y) = to;
+    fn set_pixel_at(&mut self, plane: usize, x: usize, y: usize, to: Pixel) -> Pixel {
+        let old = *self.pixel_at(plane, x, y);
+        *self.pixel_at_mut(plane, x, y) = to;
+        self.mark_dirty(DirtyRect::pixel(x, y));
         old
     }
 
-    fn xor_pixel_at(&mut self, x: usize, y: usize) -> bool {
-        if *self.pixel_at(x, y) == Pixel::Black {
-            self.set_pixel_at(x, y, Pixel::White);
+    fn xor_pixel_at(&mut self, plane: usize, x: usize, y: usize) -> bool {
+        if *self.pixel_at(plane, x, y) == Pixel::Black {
+            self.set_pixel_at(plane, x, y, Pixel::White);
             false
         } else {
-            self.set_pixel_at(x, y, Pixel::Black);
+            self.set_pixel_at(plane, x, y, Pixel::Black);
             true
         }
     }
@@ -76,6 +489,45 @@ impl Display {
     }
 }
 
+impl From<[[Pixel; 64]; 32]> for Display {
+    /// Fills the first plane in lowres, leaving the second blank and only the first selected
+    fn from(raw: [[Pixel; 64]; 32]) -> Self {
+        let mut first = BLANK_PLANE;
+        for (row, raw_row) in first.iter_mut().zip(raw) {
+            row[..LORES_WIDTH].copy_from_slice(&raw_row);
+        }
+        Self {
+            planes: [first, BLANK_PLANE],
+            selected: 0b01,
+            hires: false,
+            dirty: None,
+        }
+    }
+}
+
+impl PartialEq for Display {
+    fn eq(&self, other: &Self) -> bool {
+        self.planes == other.planes && self.selected == other.selected && self.hires == other.hires
+    }
+}
+
+impl Eq for Display {}
+
+impl From<Display> for [[Pixel; 64]; 32] {
+    fn from(display: Display) -> Self {
+        display.into_raw()
+    }
+}
+
+/// Anything that shows or saves frames, like a window or a recording. Frames can be lowres or
+/// hires, so implementations size their output from [`Display::width`] and [`Display::height`]
+/// rather than assuming 64x32.
+pub trait Frontend {
+    type Error;
+
+    fn present(&mut self, frame: &Display) -> Result<(), Self::Error>;
+}
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 #[must_use]
 pub enum ScreenModification {
@@ -95,3 +547,309 @@ impl ScreenModification {
         *self = Self::Clears;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_raw_round_trip() {
+        let mut raw = [[Pixel::Black; 64]; 32];
+        raw[3][60] = Pixel::from(true);
+        let display = Display::from(raw);
+        assert!(bool::from(display.raw()[3][60]));
+        assert_eq!(<[[Pixel; 64]; 32]>::from(display), raw);
+    }
+
+    #[test]
+    fn test_equal_drawings() {
+        let draw = || {
+            let mut display = Display::blank();
+            let _ = display.draw_sprite_bytes(10, 4, &[0xF0, 0x90, 0xF0]);
+            display
+        };
+        let (first, mut second) = (draw(), draw());
+        assert!(first.pixels_eq(&second));
+        assert_eq!(first, second);
+
+        second.set_pixel(0, 0, Pixel::White);
+        assert!(!first.pixels_eq(&second));
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn test_to_ascii() {
+        let mut display = Display::blank();
+        let _ = display.sprite(Datum(1), Datum(0), &[Datum(0b1100_0000)]);
+        let ascii = display.to_ascii();
+        assert_eq!(ascii.lines().count(), 32);
+        assert!(ascii.lines().all(|line| line.len() == 64));
+        assert!(ascii.starts_with(".##....."));
+        assert_eq!(ascii.matches('#').count(), 2);
+    }
+
+    #[test]
+    fn test_half_blocks() {
+        let mut display = Display::blank();
+        let _ = display.sprite(
+            Datum(0),
+            Datum(0),
+            &[Datum(0b1100_0000), Datum(0b1010_0000)],
+        );
+        let cells = display.to_cells();
+        assert_eq!((cells.len(), cells[0].len()), (32, 64));
+        assert_eq!(cells[1][..3], [true, false, true]);
+
+        let blocks = display.to_half_blocks();
+        assert_eq!(blocks.lines().count(), 16);
+        assert!(blocks.lines().all(|line| line.chars().count() == 64));
+        assert!(blocks.starts_with("\u{2588}\u{2580}\u{2584} "));
+        assert!(blocks.lines().skip(1).all(|line| line.trim().is_empty()));
+    }
+
+    #[test]
+    fn test_inverted_is_complement() {
+        let mut display = Display::blank();
+        let _ = display.sprite(Datum(2), Datum(1), &[Datum(0b1010_0101), Datum(0xFF)]);
+        let inverted = display.raw_inverted();
+        for (row, inverted_row) in display.raw().iter().zip(&inverted) {
+            for (pixel, inverted_pixel) in row.iter().zip(inverted_row) {
+                assert_ne!(pixel, inverted_pixel);
+            }
+        }
+        assert_eq!(inverted[1][2], Pixel::Black);
+        assert_eq!(inverted[1][3], Pixel::White);
+    }
+
+    #[test]
+    fn test_scroll_moves_half_hires_pixels() {
+        let mut display = Display::blank();
+        let _ = display.sprite(Datum(0), Datum(0), &[Datum(0b1000_0000)]);
+        let _ = display.sprite(Datum(63), Datum(31), &[Datum(0b1000_0000)]);
+
+        display.scroll_right();
+        assert!(bool::from(display.raw()[0][2]));
+        assert!(!bool::from(display.raw()[0][0]));
+        assert_eq!(display.to_ascii().matches('#').count(), 1);
+
+        display.scroll_left();
+        assert!(bool::from(display.raw()[0][0]));
+
+        display.scroll_down(1);
+        assert!(bool::from(display.raw()[0][0]));
+        display.scroll_down(5);
+        assert!(bool::from(display.raw()[2][0]));
+        assert!(!bool::from(display.raw()[0][0]));
+
+        display.scroll_down(0xF);
+        assert!(bool::from(display.raw()[9][0]));
+        display.scroll_down(0xF);
+        display.scroll_down(0xF);
+        display.scroll_down(0xF);
+        assert!(bool::from(display.raw()[30][0]));
+        display.scroll_down(0xF);
+        assert_eq!(display.to_ascii().matches('#').count(), 0);
+    }
+
+    #[test]
+    fn test_scroll_moves_whole_pixels_in_hires() {
+        let mut display = Display::blank();
+        display.set_hires(true);
+        let _ = display.sprite(Datum(0), Datum(0), &[Datum(0b1000_0000)]);
+        let _ = display.sprite(Datum(127), Datum(63), &[Datum(0b1000_0000)]);
+
+        display.scroll_right();
+        assert!(bool::from(display.raw()[0][4]));
+        assert!(!bool::from(display.raw()[0][0]));
+        assert_eq!(display.to_ascii().matches('#').count(), 1);
+
+        display.scroll_left();
+        assert!(bool::from(display.raw()[0][0]));
+
+        display.scroll_down(1);
+        assert!(bool::from(display.raw()[1][0]));
+        display.scroll_down(0xF);
+        assert!(bool::from(display.raw()[16][0]));
+        display.scroll_down(0xF);
+        display.scroll_down(0xF);
+        display.scroll_down(0xF);
+        assert!(bool::from(display.raw()[61][0]));
+        display.scroll_down(0xF);
+        assert_eq!(display.to_ascii().matches('#').count(), 0);
+    }
+
+    #[test]
+    fn test_hires() {
+        let mut display = Display::blank();
+        let _ = display.draw_sprite_bytes(0, 0, &[0b1000_0000]);
+        let _ = display.take_dirty();
+
+        display.set_hires(true);
+        assert!(display.is_hires());
+        assert_eq!(
+            (display.width(), display.height()),
+            (HIRES_WIDTH, HIRES_HEIGHT)
+        );
+        assert_eq!(display.take_dirty(), Some(DirtyRect::FULL_HIRES));
+        assert_eq!(display.to_ascii().matches('#').count(), 0);
+        let ascii = display.to_ascii();
+        assert_eq!(ascii.lines().count(), 64);
+        assert!(ascii.lines().all(|line| line.len() == 128));
+
+        assert_eq!(display.set_pixel(127, 63, Pixel::White), Some(Pixel::Black));
+        assert_eq!(display.get_pixel(128, 0), None);
+        let _ = display.draw_sprite_bytes(130, 70, &[0b1000_0000]);
+        assert_eq!(display.get_pixel(2, 6), Some(Pixel::White));
+
+        display.set_hires(false);
+        assert_eq!(display, Display::blank());
+        assert_eq!(display.raw().len(), LORES_HEIGHT);
+    }
+
+    #[test]
+    fn test_lowres_equals_upscaled_hires() {
+        let mut lowres = Display::blank();
+        let _ = lowres.draw_sprite_bytes(3, 2, &[0b1010_0000]);
+
+        let mut hires = Display::blank();
+        hires.set_hires(true);
+        let _ = hires.draw_sprite_bytes(6, 4, &[0b1100_1100, 0b1100_1100]);
+        assert!(lowres.pixels_eq(&hires));
+        assert_ne!(lowres, hires);
+
+        let _ = hires.draw_sprite_bytes(6, 4, &[0b1000_0000]);
+        assert!(!lowres.pixels_eq(&hires));
+    }
+
+    #[test]
+    fn test_frame_hash() {
+        let mut display = Display::blank();
+        let blank = display.frame_hash();
+        let _ = display.sprite(
+            Datum(10),
+            Datum(5),
+            &[Datum(0xF0), Datum(0x90), Datum(0xF0)],
+        );
+        assert_ne!(display.frame_hash(), blank);
+        assert_eq!(display.frame_hash(), 0xB5AC_AFAB_3613_1D9F);
+
+        display.clear();
+        assert_eq!(display.frame_hash(), blank);
+    }
+
+    #[test]
+    fn test_get_and_set_pixel() {
+        let mut display = Display::blank();
+        assert_eq!(display.set_pixel(63, 31, Pixel::White), Some(Pixel::Black));
+        assert_eq!(display.get_pixel(63, 31), Some(Pixel::White));
+        assert_eq!(display.to_ascii().matches('#').count(), 1);
+
+        assert_eq!(display.get_pixel(64, 0), None);
+        assert_eq!(display.get_pixel(0, 32), None);
+        assert_eq!(display.set_pixel(64, 31, Pixel::White), None);
+    }
+
+    #[test]
+    fn test_draw_sprite_bytes() {
+        let mut display = Display::blank();
+        assert_eq!(
+            display.draw_sprite_bytes(4, 2, &[0b1000_0001, 0b0100_0000]),
+            ScreenModification::Sets
+        );
+        assert_eq!(display.get_pixel(4, 2), Some(Pixel::White));
+        assert_eq!(display.get_pixel(11, 2), Some(Pixel::White));
+        assert_eq!(display.get_pixel(5, 3), Some(Pixel::White));
+        assert_eq!(display.to_ascii().matches('#').count(), 3);
+
+        assert_eq!(
+            display.draw_sprite_bytes(4, 3, &[0b0100_0000]),
+            ScreenModification::Clears
+        );
+        assert_eq!(display.get_pixel(5, 3), Some(Pixel::Black));
+    }
+
+    #[test]
+    fn test_planes() {
+        let mut display = Display::blank();
+        let hash = display.frame_hash();
+        display.select_planes(0b10);
+        assert_eq!(display.sprite_len(3), 3);
+        assert_eq!(
+            display.draw_sprite_bytes(0, 0, &[0b1100_0000]),
+            ScreenModification::Sets
+        );
+        assert_eq!(display.raw(), Display::blank().raw());
+        assert_eq!(display.color_index(0, 0), Some(0b10));
+        assert_ne!(display.frame_hash(), hash);
+
+        display.select_planes(0b11);
+        assert_eq!(display.sprite_len(3), 6);
+        assert_eq!(
+            display.draw_sprite_bytes(0, 0, &[0b1000_0000, 0b1000_0000]),
+            ScreenModification::Clears
+        );
+        assert_eq!(display.get_pixel(0, 0), Some(Pixel::White));
+        assert_eq!(display.color_index(0, 0), Some(0b01));
+        assert_eq!(display.color_index(1, 0), Some(0b10));
+        assert_eq!(display.to_color_indices()[0][..3], [0b01, 0b10, 0]);
+        assert_eq!(display.color_index(64, 0), None);
+
+        display.clear();
+        assert_eq!(display.frame_hash(), hash);
+    }
+
+    #[test]
+    fn test_sprite_too_short_for_selected_planes_draws_nothing() {
+        let mut display = Display::blank();
+        display.select_planes(0b11);
+        assert_eq!(
+            display.draw_sprite_bytes(0, 0, &[0b1000_0000]),
+            ScreenModification::Nothing
+        );
+        assert_eq!(display.color_index(0, 0), Some(0));
+        assert_eq!(display.frame_hash(), Display::blank().frame_hash());
+        assert_eq!(display.take_dirty(), None);
+    }
+
+    #[test]
+    fn test_clear_and_scroll_selected_planes() {
+        let mut display = Display::blank();
+        display.select_planes(0b11);
+        let _ = display.draw_sprite_bytes(0, 0, &[0b1000_0000, 0b1000_0000]);
+
+        display.select_planes(0b10);
+        display.scroll_right();
+        assert_eq!(display.color_index(0, 0), Some(0b01));
+        assert_eq!(display.color_index(2, 0), Some(0b10));
+        display.clear();
+        assert_eq!(display.color_index(0, 0), Some(0b01));
+        assert_eq!(display.color_index(2, 0), Some(0));
+
+        display.select_planes(0b01);
+        display.clear();
+        assert_eq!(display, Display::blank());
+    }
+
+    #[test]
+    fn test_dirty_region() {
+        let mut display = Display::blank();
+        assert_eq!(display.take_dirty(), None);
+
+        let _ = display.draw_sprite_bytes(10, 4, &[0b1000_0000, 0b0010_0000]);
+        assert_eq!(
+            display.take_dirty(),
+            Some(DirtyRect {
+                x: 10,
+                y: 4,
+                width: 3,
+                height: 2,
+            })
+        );
+        assert_eq!(display.take_dirty(), None);
+
+        display.clear();
+        assert_eq!(display.take_dirty(), Some(DirtyRect::FULL));
+        display.scroll_left();
+        assert_eq!(display.take_dirty(), Some(DirtyRect::FULL));
+    }
+}
@@ -1,7 +1,7 @@
 use crate::control::{ControlledInterpreter, FrameInfo, InterpreterState};
 use crate::key::Keys;
 use crate::memory::Memory;
-use crate::{Datum, GeneralRegister, NUMBER_OF_ADDRESSES};
+use crate::{Address, Datum, GeneralRegister, Instruction, NUMBER_OF_ADDRESSES};
 use std::fmt::Debug;
 
 pub mod disabled;
@@ -15,17 +15,62 @@ pub trait InterpreterHook<T: ControlledInterpreter>: Debug + Send {
     fn get_keys(&mut self, state: InterpreterState, int: &T, keys: Keys) -> HookedItem<Keys> {
         HookedItem::ignore()
     }
+    /// Called after `get_keys` whenever the keys differ from the previous step
+    /// `pressed` and `released` hold only the keys that changed
+    fn on_key_change(&mut self, pressed: Keys, released: Keys) {}
     /// Called immediately before a step
     /// Has access to the `FrameInfo`
     fn before_step(&mut self, int: &mut T, frame: &mut FrameInfo) {}
     /// Called immediately after a step
     /// Has access to the `FrameInfo`
     fn after_step(&mut self, int: &mut T, frame: &mut FrameInfo) {}
+    /// Called after a `0nnn` SYS instruction, which interpreters treat as a no-op
+    /// Use this to emulate the machine code routine at `address`
+    fn machine_call(&mut self, int: &mut T, address: Address) {}
+    /// Called between decoding and executing an instruction
+    /// Return a different instruction to execute instead, or `None` to skip it
+    fn on_decode(&mut self, instruction: Instruction) -> Option<Instruction> {
+        Some(instruction)
+    }
     /// Called at the very end of each step
     /// Use this to analyse something over the whole frame or flush datastreams
     fn post_cycle(&mut self, state: &mut InterpreterState) {}
 }
 
+impl<T: ControlledInterpreter, H: InterpreterHook<T> + ?Sized> InterpreterHook<T> for Box<H> {
+    fn pre_cycle(&mut self, state: &mut InterpreterState) {
+        (**self).pre_cycle(state)
+    }
+
+    fn get_keys(&mut self, state: InterpreterState, int: &T, keys: Keys) -> HookedItem<Keys> {
+        (**self).get_keys(state, int, keys)
+    }
+
+    fn on_key_change(&mut self, pressed: Keys, released: Keys) {
+        (**self).on_key_change(pressed, released)
+    }
+
+    fn before_step(&mut self, int: &mut T, frame: &mut FrameInfo) {
+        (**self).before_step(int, frame)
+    }
+
+    fn after_step(&mut self, int: &mut T, frame: &mut FrameInfo) {
+        (**self).after_step(int, frame)
+    }
+
+    fn machine_call(&mut self, int: &mut T, address: Address) {
+        (**self).machine_call(int, address)
+    }
+
+    fn on_decode(&mut self, instruction: Instruction) -> Option<Instruction> {
+        (**self).on_decode(instruction)
+    }
+
+    fn post_cycle(&mut self, state: &mut InterpreterState) {
+        (**self).post_cycle(state)
+    }
+}
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum FurtherHooks {
     Continue,
@@ -78,6 +123,15 @@ pub trait HookInternalAccess<I> {
         frame.wait_for_key
     }
 
+    fn is_halted(&self, frame: &FrameInfo) -> bool {
+        frame.halted
+    }
+
+    /// Bit `n` is set if `Vn` was changed this step
+    fn changed_registers(&self, frame: &FrameInfo) -> u16 {
+        frame.changed_registers
+    }
+
     fn extract_memory(&self, memory: Memory) -> [Datum; NUMBER_OF_ADDRESSES] {
         memory.extract()
     }
@@ -1,7 +1,10 @@
 use crate::control::{ControlledInterpreter, FrameInfo, InterpreterState};
 use crate::hooks::{HookedItem, InterpreterHook};
 use crate::key::Keys;
+use crate::{Address, Instruction};
 
+/// Wraps a hook so it can be switched off and on again without removing it
+/// Every hook on an `Interpreter` is held in one of these, see `Interpreter::hook_mut`
 #[derive(Debug)]
 pub struct EnabledHook<I> {
     inner: I,
@@ -9,6 +12,17 @@ pub struct EnabledHook<I> {
 }
 
 impl<I> EnabledHook<I> {
+    pub fn new(inner: I) -> Self {
+        Self {
+            inner,
+            enabled: true,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
     fn inner(&mut self) -> Option<&mut I> {
         if self.enabled {
             Some(&mut self.inner)
@@ -33,6 +47,12 @@ impl<T: ControlledInterpreter, I: InterpreterHook<T>> InterpreterHook<T> for Ena
             .unwrap_or_else(HookedItem::ignore)
     }
 
+    fn on_key_change(&mut self, pressed: Keys, released: Keys) {
+        if let Some(i) = self.inner() {
+            i.on_key_change(pressed, released)
+        }
+    }
+
     fn pre_cycle(&mut self, state: &mut InterpreterState) {
         if let Some(i) = self.inner() {
             i.pre_cycle(state)
@@ -51,9 +71,22 @@ impl<T: ControlledInterpreter, I: InterpreterHook<T>> InterpreterHook<T> for Ena
         }
     }
 
+    fn machine_call(&mut self, int: &mut T, address: Address) {
+        if let Some(i) = self.inner() {
+            i.machine_call(int, address)
+        }
+    }
+
+    fn on_decode(&mut self, instruction: Instruction) -> Option<Instruction> {
+        match self.inner() {
+            Some(i) => i.on_decode(instruction),
+            None => Some(instruction),
+        }
+    }
+
     fn post_cycle(&mut self, state: &mut InterpreterState) {
         if let Some(i) = self.inner() {
-            i.pre_cycle(state)
+            i.post_cycle(state)
         }
     }
 }
@@ -1,11 +1,17 @@
 use crate::data::Nibble;
 use crate::{Address, Datum, GeneralRegister as VX};
+use thiserror::Error;
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum Instruction {
     /// 0000 (Not a standard instruction)
     /// Does nothing
+    /// Zeroed memory decodes to this, so a strict interpreter treats it as running off the end of the program and halts.
     Nop,
+    /// 0nnn
+    /// Calls the machine code routine at _nnn_ on the original hardware.
+    /// Interpreters ignore it, but an `InterpreterHook` may emulate the routine.
+    Sys(Address),
     /// 00E0
     /// Clears the screen (all pixels to black)
     ClearScreen,
@@ -55,13 +61,15 @@ pub enum Instruction {
     Sub { x: VX, y: VX },
     /// 8xy6
     /// If the least-significant bit of Vx is 1, then VF is set to 1, otherwise 0. Then Vx is divided by 2.
-    Shr(VX),
+    /// The original COSMAC VIP shifted Vy into Vx instead, see `Quirks`.
+    Shr { x: VX, y: VX },
     /// 8xy7
     /// If Vy > Vx, then VF is set to 1, otherwise 0. Then Vx is subtracted from Vy, and the results stored in Vx.
     SubN { x: VX, y: VX },
     /// 8xyE
     /// If the most-significant bit of Vx is 1, then VF is set to 1, otherwise to 0. Then Vx is multiplied by 2.
-    Shl(VX),
+    /// The original COSMAC VIP shifted Vy into Vx instead, see `Quirks`.
+    Shl { x: VX, y: VX },
     /// 9xy0
     /// The interpreter compares register Vx to register Vy, and if they are not equal, increments the program counter by 2.
     SkipRegistersNotEqual(VX, VX),
@@ -118,11 +126,16 @@ pub enum Instruction {
     /// Fx65
     /// The interpreter reads values from memory starting at location I into registers V0 through Vx.
     ReadMultiple(VX),
+    /// Fn01 (XO-CHIP)
+    /// Selects the display planes drawn into, with bit 0 of _n_ for the first plane and bit 1 for the second.
+    SelectPlanes(u8),
 }
 
 impl Instruction {
     pub fn try_from_data(data: RawInstruction) -> Result<Self, InstructionDecodeError> {
         let [n1, n2, n3, n4] = data.as_nibbles();
+        // Operand registers always come from a nibble, so can't be out of range
+        let (vx, vy) = (VX::from_nibble(n2), VX::from_nibble(n3));
         match [
             n1.as_half_byte(),
             n2.as_half_byte(),
@@ -132,74 +145,288 @@ impl Instruction {
             [0x0, 0x0, 0x0, 0x0] => Ok(Self::Nop),
             [0x0, 0x0, 0xE, 0x0] => Ok(Self::ClearScreen),
             [0x0, 0x0, 0xE, 0xE] => Ok(Self::Return),
+            [0x0, a1, a2, a3] => Ok(Self::Sys(Address::from_triplet(a1, a2, a3))),
             [0x1, a1, a2, a3] => Ok(Self::Jump(Address::from_triplet(a1, a2, a3))),
             [0x2, a1, a2, a3] => Ok(Self::Call(Address::from_triplet(a1, a2, a3))),
-            [0x3, x, b1, b2] => Ok(Self::SkipIfEqual(VX::from_byte(x), byte_with(b1, b2))),
-            [0x4, x, b1, b2] => Ok(Self::SkipNotEqual(VX::from_byte(x), byte_with(b1, b2))),
-            [0x5, x, y, 0x0] => Ok(Self::SkipRegistersEqual(VX::from_byte(x), VX::from_byte(y))),
-            [0x6, x, b1, b2] => Ok(Self::LoadRegByte(VX::from_byte(x), byte_with(b1, b2))),
-            [0x7, x, b1, b2] => Ok(Self::Add(VX::from_byte(x), byte_with(b1, b2))),
-            [0x8, x, y, 0x0] => Ok(Self::CopyRegToReg {
-                x: VX::from_byte(x),
-                y: VX::from_byte(y),
-            }),
-            [0x8, x, y, 0x1] => Ok(Self::Or {
-                x: VX::from_byte(x),
-                y: VX::from_byte(y),
-            }),
-            [0x8, x, y, 0x2] => Ok(Self::And {
-                x: VX::from_byte(x),
-                y: VX::from_byte(y),
-            }),
-            [0x8, x, y, 0x3] => Ok(Self::Xor {
-                x: VX::from_byte(x),
-                y: VX::from_byte(y),
-            }),
-            [0x8, x, y, 0x4] => Ok(Self::AddReg {
-                x: VX::from_byte(x),
-                y: VX::from_byte(y),
-            }),
-            [0x8, x, y, 0x5] => Ok(Self::Sub {
-                x: VX::from_byte(x),
-                y: VX::from_byte(y),
-            }),
-            [0x8, x, _, 0x6] => Ok(Self::Shr(VX::from_byte(x))),
-            [0x8, x, y, 0x7] => Ok(Self::SubN {
-                x: VX::from_byte(x),
-                y: VX::from_byte(y),
-            }),
-            [0x8, x, _, 0xE] => Ok(Self::Shl(VX::from_byte(x))),
-            [0x9, x, y, 0x0] => Ok(Self::SkipRegistersNotEqual(
-                VX::from_byte(x),
-                VX::from_byte(y),
-            )),
+            [0x3, _, b1, b2] => Ok(Self::SkipIfEqual(vx, byte_with(b1, b2)?)),
+            [0x4, _, b1, b2] => Ok(Self::SkipNotEqual(vx, byte_with(b1, b2)?)),
+            [0x5, _, _, 0x0] => Ok(Self::SkipRegistersEqual(vx, vy)),
+            [0x6, _, b1, b2] => Ok(Self::LoadRegByte(vx, byte_with(b1, b2)?)),
+            [0x7, _, b1, b2] => Ok(Self::Add(vx, byte_with(b1, b2)?)),
+            [0x8, _, _, 0x0] => Ok(Self::CopyRegToReg { x: vx, y: vy }),
+            [0x8, _, _, 0x1] => Ok(Self::Or { x: vx, y: vy }),
+            [0x8, _, _, 0x2] => Ok(Self::And { x: vx, y: vy }),
+            [0x8, _, _, 0x3] => Ok(Self::Xor { x: vx, y: vy }),
+            [0x8, _, _, 0x4] => Ok(Self::AddReg { x: vx, y: vy }),
+            [0x8, _, _, 0x5] => Ok(Self::Sub { x: vx, y: vy }),
+            [0x8, _, _, 0x6] => Ok(Self::Shr { x: vx, y: vy }),
+            [0x8, _, _, 0x7] => Ok(Self::SubN { x: vx, y: vy }),
+            [0x8, _, _, 0xE] => Ok(Self::Shl { x: vx, y: vy }),
+            [0x9, _, _, 0x0] => Ok(Self::SkipRegistersNotEqual(vx, vy)),
             [0xA, a1, a2, a3] => Ok(Self::LoadImmediate(Address::from_triplet(a1, a2, a3))),
             [0xB, a1, a2, a3] => Ok(Self::JumpRelative(Address::from_triplet(a1, a2, a3))),
-            [0xC, x, b1, b2] => Ok(Self::Random(VX::from_byte(x), byte_with(b1, b2))),
-            [0xD, x, y, n] => Ok(Self::DisplaySprite {
-                x: VX::from_byte(x),
-                y: VX::from_byte(y),
+            [0xC, _, b1, b2] => Ok(Self::Random(vx, byte_with(b1, b2)?)),
+            [0xD, _, _, n] => Ok(Self::DisplaySprite {
+                x: vx,
+                y: vy,
                 number_of_bytes: n,
             }),
-            [0xE, x, 0x9, 0xE] => Ok(Self::SkipPressed(VX::from_byte(x))),
-            [0xE, x, 0xA, 0x1] => Ok(Self::SkipNotPressed(VX::from_byte(x))),
-            [0xF, x, 0x0, 0x7] => Ok(Self::GetDelayTimer(VX::from_byte(x))),
-            [0xF, x, 0x0, 0xA] => Ok(Self::WaitForKey(VX::from_byte(x))),
-            [0xF, x, 0x1, 0x5] => Ok(Self::SetDelayTimer(VX::from_byte(x))),
-            [0xF, x, 0x1, 0x8] => Ok(Self::SetSoundTimer(VX::from_byte(x))),
-            [0xF, x, 0x1, 0xE] => Ok(Self::AddI(VX::from_byte(x))),
-            [0xF, x, 0x2, 0x9] => Ok(Self::GetSprite(VX::from_byte(x))),
-            [0xF, x, 0x3, 0x3] => Ok(Self::BCD(VX::from_byte(x))),
-            [0xF, x, 0x5, 0x5] => Ok(Self::WriteMultiple(VX::from_byte(x))),
-            [0xF, x, 0x6, 0x5] => Ok(Self::ReadMultiple(VX::from_byte(x))),
-            _ => Err(InstructionDecodeError::InvalidInstruction(data)),
+            [0xE, _, 0x9, 0xE] => Ok(Self::SkipPressed(vx)),
+            [0xE, _, 0xA, 0x1] => Ok(Self::SkipNotPressed(vx)),
+            [0xF, n, 0x0, 0x1] => Ok(Self::SelectPlanes(n)),
+            [0xF, _, 0x0, 0x7] => Ok(Self::GetDelayTimer(vx)),
+            [0xF, _, 0x0, 0xA] => Ok(Self::WaitForKey(vx)),
+            [0xF, _, 0x1, 0x5] => Ok(Self::SetDelayTimer(vx)),
+            [0xF, _, 0x1, 0x8] => Ok(Self::SetSoundTimer(vx)),
+            [0xF, _, 0x1, 0xE] => Ok(Self::AddI(vx)),
+            [0xF, _, 0x2, 0x9] => Ok(Self::GetSprite(vx)),
+            [0xF, _, 0x3, 0x3] => Ok(Self::BCD(vx)),
+            [0xF, _, 0x5, 0x5] => Ok(Self::WriteMultiple(vx)),
+            [0xF, _, 0x6, 0x5] => Ok(Self::ReadMultiple(vx)),
+            _ => Err(InstructionDecodeError::invalid(data)),
+        }
+    }
+
+    /// Like `try_from_data`, but a failure also reports the nibbles and which family of opcodes
+    /// the leading nibble selected, for debugging malformed ROMs
+    pub fn try_from_data_verbose(data: RawInstruction) -> Result<Self, DecodeTrace> {
+        Self::try_from_data(data).map_err(|error| {
+            let nibbles = data.as_nibbles().map(|nibble| nibble.as_half_byte());
+            let family = match nibbles[0] {
+                0x5 => "5xy0",
+                0x8 => "8xyN",
+                0x9 => "9xy0",
+                0xE => "ExNN",
+                0xF => "FxNN",
+                // Every other leading nibble decodes at the moment, but a new opcode shouldn't
+                // make a bad ROM panic while it's being reported
+                _ => "unknown",
+            };
+            DecodeTrace {
+                nibbles,
+                family,
+                error,
+            }
+        })
+    }
+
+    /// Whether this instruction can move the program counter anywhere other than the next instruction
+    pub fn is_control_flow(self) -> bool {
+        matches!(
+            self,
+            Self::Return
+                | Self::Jump(_)
+                | Self::Call(_)
+                | Self::JumpRelative(_)
+                | Self::SkipIfEqual(_, _)
+                | Self::SkipNotEqual(_, _)
+                | Self::SkipRegistersEqual(_, _)
+                | Self::SkipRegistersNotEqual(_, _)
+                | Self::SkipPressed(_)
+                | Self::SkipNotPressed(_)
+        )
+    }
+
+    /// Whether execution never carries on to the following instruction, as at the end of a program
+    /// `Call` isn't, as execution comes back to the next instruction when the subroutine returns
+    pub fn is_terminating(self) -> bool {
+        matches!(self, Self::Return | Self::Jump(_) | Self::JumpRelative(_))
+    }
+
+    /// Whether this instruction may skip over the one following it
+    pub fn is_skip(self) -> bool {
+        matches!(
+            self,
+            Self::SkipIfEqual(_, _)
+                | Self::SkipNotEqual(_, _)
+                | Self::SkipRegistersEqual(_, _)
+                | Self::SkipRegistersNotEqual(_, _)
+                | Self::SkipPressed(_)
+                | Self::SkipNotPressed(_)
+        )
+    }
+
+    /// The statically known destination of a jump or call
+    /// `JumpRelative` depends on `V0`, so it has no static target
+    pub fn jump_target(self) -> Option<Address> {
+        match self {
+            Self::Jump(addr) | Self::Call(addr) => Some(addr),
+            _ => None,
+        }
+    }
+
+    /// The name of every variant, in declaration order
+    pub const VARIANT_NAMES: [&'static str; 37] = [
+        "Nop",
+        "Sys",
+        "ClearScreen",
+        "Return",
+        "Jump",
+        "Call",
+        "SkipIfEqual",
+        "SkipNotEqual",
+        "SkipRegistersEqual",
+        "LoadRegByte",
+        "Add",
+        "CopyRegToReg",
+        "Or",
+        "And",
+        "Xor",
+        "AddReg",
+        "Sub",
+        "Shr",
+        "SubN",
+        "Shl",
+        "SkipRegistersNotEqual",
+        "LoadImmediate",
+        "JumpRelative",
+        "Random",
+        "DisplaySprite",
+        "SkipPressed",
+        "SkipNotPressed",
+        "GetDelayTimer",
+        "WaitForKey",
+        "SetDelayTimer",
+        "SetSoundTimer",
+        "AddI",
+        "GetSprite",
+        "BCD",
+        "WriteMultiple",
+        "ReadMultiple",
+        "SelectPlanes",
+    ];
+
+    /// The name of the variant, without its operands, as listed in `VARIANT_NAMES`
+    pub fn variant_name(self) -> &'static str {
+        match self {
+            Self::Nop => "Nop",
+            Self::Sys(_) => "Sys",
+            Self::ClearScreen => "ClearScreen",
+            Self::Return => "Return",
+            Self::Jump(_) => "Jump",
+            Self::Call(_) => "Call",
+            Self::SkipIfEqual(_, _) => "SkipIfEqual",
+            Self::SkipNotEqual(_, _) => "SkipNotEqual",
+            Self::SkipRegistersEqual(_, _) => "SkipRegistersEqual",
+            Self::LoadRegByte(_, _) => "LoadRegByte",
+            Self::Add(_, _) => "Add",
+            Self::CopyRegToReg { .. } => "CopyRegToReg",
+            Self::Or { .. } => "Or",
+            Self::And { .. } => "And",
+            Self::Xor { .. } => "Xor",
+            Self::AddReg { .. } => "AddReg",
+            Self::Sub { .. } => "Sub",
+            Self::Shr { .. } => "Shr",
+            Self::SubN { .. } => "SubN",
+            Self::Shl { .. } => "Shl",
+            Self::SkipRegistersNotEqual(_, _) => "SkipRegistersNotEqual",
+            Self::LoadImmediate(_) => "LoadImmediate",
+            Self::JumpRelative(_) => "JumpRelative",
+            Self::Random(_, _) => "Random",
+            Self::DisplaySprite { .. } => "DisplaySprite",
+            Self::SkipPressed(_) => "SkipPressed",
+            Self::SkipNotPressed(_) => "SkipNotPressed",
+            Self::GetDelayTimer(_) => "GetDelayTimer",
+            Self::WaitForKey(_) => "WaitForKey",
+            Self::SetDelayTimer(_) => "SetDelayTimer",
+            Self::SetSoundTimer(_) => "SetSoundTimer",
+            Self::AddI(_) => "AddI",
+            Self::GetSprite(_) => "GetSprite",
+            Self::BCD(_) => "BCD",
+            Self::WriteMultiple(_) => "WriteMultiple",
+            Self::ReadMultiple(_) => "ReadMultiple",
+            Self::SelectPlanes(_) => "SelectPlanes",
         }
     }
 
+    /// The conventional assembly mnemonic, as in Cowgod's reference
+    pub fn mnemonic(self) -> &'static str {
+        match self {
+            Self::Nop => "nop",
+            Self::Sys(_) => "sys",
+            Self::ClearScreen => "cls",
+            Self::Return => "ret",
+            Self::Jump(_) | Self::JumpRelative(_) => "jp",
+            Self::Call(_) => "call",
+            Self::SkipIfEqual(_, _) | Self::SkipRegistersEqual(_, _) => "se",
+            Self::SkipNotEqual(_, _) | Self::SkipRegistersNotEqual(_, _) => "sne",
+            Self::LoadRegByte(_, _)
+            | Self::CopyRegToReg { .. }
+            | Self::LoadImmediate(_)
+            | Self::GetDelayTimer(_)
+            | Self::WaitForKey(_)
+            | Self::SetDelayTimer(_)
+            | Self::SetSoundTimer(_)
+            | Self::GetSprite(_)
+            | Self::BCD(_)
+            | Self::WriteMultiple(_)
+            | Self::ReadMultiple(_) => "ld",
+            Self::Add(_, _) | Self::AddReg { .. } | Self::AddI(_) => "add",
+            Self::Or { .. } => "or",
+            Self::And { .. } => "and",
+            Self::Xor { .. } => "xor",
+            Self::Sub { .. } => "sub",
+            Self::Shr { .. } => "shr",
+            Self::SubN { .. } => "subn",
+            Self::Shl { .. } => "shl",
+            Self::Random(_, _) => "rnd",
+            Self::DisplaySprite { .. } => "drw",
+            Self::SkipPressed(_) => "skp",
+            Self::SkipNotPressed(_) => "sknp",
+            Self::SelectPlanes(_) => "plane",
+        }
+    }
+
+    /// Formats the instruction as source accepted by `c8asm`
+    pub fn to_asm(self) -> String {
+        let reg = |reg: VX| format!("v{:x}", reg.index());
+        let addr = |addr: Address| format!("0x{:03X}", addr.as_u16());
+        let operands = match self {
+            Self::Nop | Self::ClearScreen | Self::Return => return self.mnemonic().to_string(),
+            Self::Sys(target) | Self::Jump(target) | Self::Call(target) => addr(target),
+            Self::JumpRelative(target) => format!("v0, {}", addr(target)),
+            Self::LoadImmediate(target) => format!("i, {}", addr(target)),
+            Self::SkipIfEqual(x, byte)
+            | Self::SkipNotEqual(x, byte)
+            | Self::LoadRegByte(x, byte)
+            | Self::Add(x, byte)
+            | Self::Random(x, byte) => format!("{}, 0x{:02X}", reg(x), byte),
+            Self::SkipRegistersEqual(x, y)
+            | Self::SkipRegistersNotEqual(x, y)
+            | Self::CopyRegToReg { x, y }
+            | Self::Or { x, y }
+            | Self::And { x, y }
+            | Self::Xor { x, y }
+            | Self::AddReg { x, y }
+            | Self::Sub { x, y }
+            | Self::SubN { x, y }
+            | Self::Shr { x, y }
+            | Self::Shl { x, y } => format!("{}, {}", reg(x), reg(y)),
+            Self::SkipPressed(x) | Self::SkipNotPressed(x) => reg(x),
+            Self::DisplaySprite {
+                x,
+                y,
+                number_of_bytes,
+            } => format!("{}, {}, {}", reg(x), reg(y), number_of_bytes),
+            Self::GetDelayTimer(x) => format!("{}, dt", reg(x)),
+            Self::WaitForKey(x) => format!("{}, k", reg(x)),
+            Self::SetDelayTimer(x) => format!("dt, {}", reg(x)),
+            Self::SetSoundTimer(x) => format!("st, {}", reg(x)),
+            Self::AddI(x) => format!("i, {}", reg(x)),
+            Self::GetSprite(x) => format!("f, {}", reg(x)),
+            Self::BCD(x) => format!("b, {}", reg(x)),
+            Self::WriteMultiple(x) => format!("i, {}", reg(x)),
+            Self::ReadMultiple(x) => format!("{}, i", reg(x)),
+            Self::SelectPlanes(mask) => mask.to_string(),
+        };
+        format!("{} {}", self.mnemonic(), operands)
+    }
+
     pub fn to_data(self) -> RawInstruction {
         use conversion::ConvertToRaw;
         match self {
             Self::Nop => 0x0000_u16.to_raw(),
+            Self::Sys(addr) => (0x0, addr).to_raw(),
             Self::ClearScreen => 0x00E0_u16.to_raw(),
             Self::Return => 0x00EE_u16.to_raw(),
             Self::Jump(addr) => (0x1, addr).to_raw(),
@@ -215,9 +442,9 @@ impl Instruction {
             Self::Xor { x, y } => (0x8, x, y, 3).to_raw(),
             Self::AddReg { x, y } => (0x8, x, y, 4).to_raw(),
             Self::Sub { x, y } => (0x8, x, y, 5).to_raw(),
-            Self::Shr(reg) => (0x8, reg, 0x0, 0x6).to_raw(),
+            Self::Shr { x, y } => (0x8, x, y, 0x6).to_raw(),
             Self::SubN { x, y } => (0x8, x, y, 7).to_raw(),
-            Self::Shl(reg) => (0x8, reg, 0x0, 0xE).to_raw(),
+            Self::Shl { x, y } => (0x8, x, y, 0xE).to_raw(),
             Self::SkipRegistersNotEqual(r1, r2) => (0x9, r1, r2, 0).to_raw(),
             Self::LoadImmediate(value) => (0xA, value).to_raw(),
             Self::JumpRelative(addr) => (0xB, addr).to_raw(),
@@ -238,6 +465,7 @@ impl Instruction {
             Self::BCD(reg) => (0xF, reg, 0x33).to_raw(),
             Self::WriteMultiple(reg) => (0xF, reg, 0x55).to_raw(),
             Self::ReadMultiple(reg) => (0xF, reg, 0x65).to_raw(),
+            Self::SelectPlanes(mask) => (0xF, mask, 0, 1).to_raw(),
         }
     }
 }
@@ -274,6 +502,15 @@ impl RawInstruction {
     pub fn second(&self) -> Datum {
         self.0[1]
     }
+
+    /// Big-endian, as stored in memory
+    pub fn to_bytes(&self) -> [u8; 2] {
+        self.0.map(Datum::inner)
+    }
+
+    pub fn to_u16(&self) -> u16 {
+        u16::from_be_bytes(self.to_bytes())
+    }
 }
 
 impl From<u16> for RawInstruction {
@@ -300,24 +537,84 @@ impl From<(Datum, Datum)> for RawInstruction {
     }
 }
 
-#[derive(Debug, Clone, Eq, PartialEq)]
+#[derive(Debug, Clone, Eq, PartialEq, Error)]
 #[allow(missing_copy_implementations)]
 pub enum InstructionDecodeError {
-    InvalidInstruction(RawInstruction),
+    #[error(
+        "Invalid instruction 0x{:02X}{:02X}{}",
+        .data.first(),
+        .data.second(),
+        .suggestion.map(|s| format!(" (did you mean {}?)", s)).unwrap_or_default()
+    )]
+    InvalidInstruction {
+        data: RawInstruction,
+        suggestion: Option<&'static str>,
+    },
+    #[error("0x{:02X} is too wide for a nibble", .0)]
+    NibbleOutOfRange(u8),
 }
 
 impl InstructionDecodeError {
+    fn invalid(data: RawInstruction) -> Self {
+        Self::InvalidInstruction {
+            data,
+            suggestion: Self::suggest(data),
+        }
+    }
+
+    /// The nearest valid instructions sharing the same leading nibble
+    fn suggest(data: RawInstruction) -> Option<&'static str> {
+        let [n1, _, _, _] = data.as_nibbles();
+        match n1.as_half_byte() {
+            0x5 => Some("5xy0 (SE Vx, Vy)"),
+            0x8 => Some("one of 8xy0-8xy7 or 8xyE (register operations)"),
+            0x9 => Some("9xy0 (SNE Vx, Vy)"),
+            0xE => Some("Ex9E (SKP Vx) or ExA1 (SKNP Vx)"),
+            0xF => Some("one of Fn01, Fx07, Fx0A, Fx15, Fx18, Fx1E, Fx29, Fx33, Fx55 or Fx65"),
+            _ => None,
+        }
+    }
+
     pub fn invalid_data(self) -> Option<RawInstruction> {
         match self {
-            Self::InvalidInstruction(inner) => Some(inner),
+            Self::InvalidInstruction { data, .. } => Some(data),
+            Self::NibbleOutOfRange(_) => None,
         }
     }
+
+    pub fn suggestion(&self) -> Option<&'static str> {
+        match self {
+            Self::InvalidInstruction { suggestion, .. } => *suggestion,
+            Self::NibbleOutOfRange(_) => None,
+        }
+    }
+}
+
+/// A failed decode with the context it happened in, from `Instruction::try_from_data_verbose`
+#[derive(Debug, Clone, Eq, PartialEq, Error)]
+#[error(
+    "{} (nibbles {:X} {:X} {:X} {:X} matched the {} arm, but none of its variants)",
+    .error,
+    .nibbles[0],
+    .nibbles[1],
+    .nibbles[2],
+    .nibbles[3],
+    .family
+)]
+pub struct DecodeTrace {
+    pub nibbles: [u8; 4],
+    /// The pattern of the arm chosen by the leading nibble, such as `8xyN`, or `unknown` if none is
+    /// known to reject words
+    pub family: &'static str,
+    pub error: InstructionDecodeError,
 }
 
-fn byte_with(a: u8, b: u8) -> u8 {
-    assert_eq!(a & 0xF0, 0x00);
-    assert_eq!(b & 0xF0, 0x00);
-    a << 4 | b
+/// Joins two half-bytes, `high` first, for validating words nibble by nibble
+pub fn byte_with(high: u8, low: u8) -> Result<u8, InstructionDecodeError> {
+    let nibble = |half: u8| {
+        Nibble::try_from_half_byte(half).ok_or(InstructionDecodeError::NibbleOutOfRange(half))
+    };
+    Ok(nibble(high)?.byte_with(nibble(low)?))
 }
 
 mod conversion {
@@ -414,4 +711,127 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_decode_error_suggestions() {
+        let decode = |raw: u16| Instruction::try_from_data(raw.into()).unwrap_err();
+
+        let error = decode(0x5121);
+        assert_eq!(error.suggestion(), Some("5xy0 (SE Vx, Vy)"));
+        assert_eq!(
+            error.to_string(),
+            "Invalid instruction 0x5121 (did you mean 5xy0 (SE Vx, Vy)?)"
+        );
+        assert_eq!(
+            decode(0x8AB8).suggestion(),
+            Some("one of 8xy0-8xy7 or 8xyE (register operations)")
+        );
+        assert_eq!(
+            decode(0xE3FF).suggestion(),
+            Some("Ex9E (SKP Vx) or ExA1 (SKNP Vx)")
+        );
+        assert_eq!(decode(0xF0FF).invalid_data(), Some(0xF0FF.into()));
+    }
+
+    #[test]
+    fn test_verbose_decode() {
+        let trace = Instruction::try_from_data_verbose(0x8AB8.into()).unwrap_err();
+        assert_eq!(trace.nibbles, [0x8, 0xA, 0xB, 0x8]);
+        assert_eq!(trace.family, "8xyN");
+        assert_eq!(trace.error.clone().invalid_data(), Some(0x8AB8.into()));
+        assert!(trace
+            .to_string()
+            .ends_with("(nibbles 8 A B 8 matched the 8xyN arm, but none of its variants)"));
+        assert_eq!(
+            Instruction::try_from_data_verbose(0x00E0.into()),
+            Ok(Instruction::ClearScreen)
+        );
+    }
+
+    #[test]
+    fn test_registers_from_every_nibble() {
+        for index in 0..16u8 {
+            let register = VX::from_nibble(Nibble::new_from_half_byte(index));
+            assert_eq!(register.index(), index as usize);
+            assert_eq!(register.to_nibble().as_half_byte(), index);
+
+            let raw = 0x8004 | (index as u16) << 8 | ((15 - index) as u16) << 4;
+            assert_eq!(
+                Instruction::try_from_data(raw.into()),
+                Ok(Instruction::AddReg {
+                    x: register,
+                    y: VX::from_byte(15 - index),
+                })
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_nibble_rejects_wide_values() {
+        Nibble::new_from_half_byte(0x10);
+    }
+
+    #[test]
+    fn test_raw_to_u16_round_trip() {
+        for word in [0x0000, 0x00E0, 0xD12F, 0xFFFF] {
+            let raw = RawInstruction::from(word);
+            assert_eq!(raw.to_u16(), word);
+            assert_eq!(RawInstruction::from(raw.to_u16()), raw);
+            assert_eq!(raw.to_bytes(), word.to_be_bytes());
+            assert_eq!(RawInstruction::from_raw_bytes(raw.to_bytes()), raw);
+        }
+    }
+
+    #[test]
+    fn test_byte_with_checks_nibbles() {
+        assert_eq!(byte_with(0xA, 0x5), Ok(0xA5));
+        assert_eq!(
+            byte_with(0x1F, 0x0),
+            Err(InstructionDecodeError::NibbleOutOfRange(0x1F))
+        );
+        assert_eq!(
+            byte_with(0x0, 0x10),
+            Err(InstructionDecodeError::NibbleOutOfRange(0x10))
+        );
+        assert_eq!(Nibble::try_from_half_byte(0x10), None);
+        assert_eq!(
+            Nibble::try_from_half_byte(0xF).map(|n| n.as_half_byte()),
+            Some(0xF)
+        );
+    }
+
+    #[test]
+    fn test_sys_round_trip() {
+        let sys = Instruction::try_from_data(0x0123.into()).unwrap();
+        assert_eq!(sys, Instruction::Sys(Address::new(0x123)));
+        assert_eq!(sys.to_data(), 0x0123.into());
+        assert_eq!(sys.to_asm(), "sys 0x123");
+        assert_eq!(
+            Instruction::try_from_data(0x00E0.into()),
+            Ok(Instruction::ClearScreen)
+        );
+
+        // These three share their encoding with `nop`, `cls` and `ret`, so the assembler
+        // refuses them
+        for address in (0..=Address::MAX.as_u16()).filter(|a| ![0x000, 0x0E0, 0x0EE].contains(a)) {
+            let sys = Instruction::Sys(Address::new(address));
+            assert_eq!(Instruction::try_from_data(sys.to_data()), Ok(sys));
+        }
+    }
+
+    #[test]
+    fn test_variant_names() {
+        let mut decoded = std::collections::BTreeSet::new();
+        for word in 0..=u16::MAX {
+            if let Ok(instruction) = Instruction::try_from_data(word.into()) {
+                let name = instruction.variant_name();
+                assert!(format!("{:?}", instruction).starts_with(name));
+                decoded.insert(name);
+            }
+        }
+        let mut listed = Instruction::VARIANT_NAMES.to_vec();
+        listed.sort_unstable();
+        assert!(decoded.into_iter().eq(listed));
+    }
 }
@@ -1,5 +1,6 @@
 use crate::Datum;
 use std::ops::{BitAnd, BitOrAssign};
+use thiserror::Error;
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub struct Keys(u16);
@@ -47,6 +48,37 @@ impl Keys {
         self.0 != 0
     }
 
+    /// The keys held now that weren't held in `previous`. Swap the two for the keys released.
+    pub fn pressed_since(self, previous: Self) -> Self {
+        Self(self.0 & !previous.0)
+    }
+
+    /// Parses comma-separated hex key names, such as `1,A,F`
+    pub fn from_spec(spec: &str) -> Result<Self, InvalidKeyName> {
+        let mut keys = Self(0);
+        for name in spec
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+        {
+            let key = match u8::from_str_radix(name, 16) {
+                Ok(key) if name.len() == 1 => key,
+                _ => return Err(InvalidKeyName(name.to_string())),
+            };
+            keys |= Self::from_number(key);
+        }
+        Ok(keys)
+    }
+
+    /// The inverse of `from_spec`, listing pressed keys in ascending order
+    pub fn to_spec(&self) -> String {
+        (0..16)
+            .filter(|&i| *self & nth_shift(i) == nth_shift(i))
+            .map(|i| format!("{:X}", i))
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
     pub fn one_key(&self) -> Option<Datum> {
         for i in 0..16 {
             if *self == nth_shift(i) {
@@ -74,3 +106,35 @@ impl BitAnd for Keys {
 const fn nth_shift(n: usize) -> Keys {
     Keys(0b1 << n)
 }
+
+#[derive(Debug, Clone, Eq, PartialEq, Error)]
+#[error("'{0}' is not a key name, expected a single hex digit")]
+pub struct InvalidKeyName(pub String);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spec_round_trip() {
+        let keys = Keys::from_spec("1, a,F").unwrap();
+        assert_eq!(keys, Keys(0b1000_0100_0000_0010));
+        assert_eq!(keys.to_spec(), "1,A,F");
+        assert_eq!(Keys::from_spec(&keys.to_spec()), Ok(keys));
+        assert_eq!(Keys::from_spec(""), Ok(Keys(0)));
+    }
+
+    #[test]
+    fn test_pressed_since() {
+        let (before, after) = (Keys(0b0110), Keys(0b0011));
+        assert_eq!(after.pressed_since(before), Keys(0b0001));
+        assert_eq!(before.pressed_since(after), Keys(0b0100));
+        assert!(!after.pressed_since(after).pressed());
+    }
+
+    #[test]
+    fn test_spec_invalid_key() {
+        assert_eq!(Keys::from_spec("1,G"), Err(InvalidKeyName("G".to_string())));
+        assert!(Keys::from_spec("10").is_err());
+    }
+}
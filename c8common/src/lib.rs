@@ -3,12 +3,12 @@
 
 mod address;
 
-pub use address::Address;
+pub use address::{Address, AddressOutOfRange};
 
 pub const NUMBER_OF_ADDRESSES: usize = Address::NUMBER_OF_ADDRESSES;
 
 pub mod instruction;
-pub use instruction::{Instruction, InstructionDecodeError, RawInstruction};
+pub use instruction::{DecodeTrace, Instruction, InstructionDecodeError, RawInstruction};
 
 mod data;
 use crate::data::Nibble;
@@ -52,50 +52,78 @@ impl GeneralRegister {
         self as usize
     }
 
+    /// Infallible, as a nibble is always a valid register index
     pub fn from_nibble(nibble: Nibble) -> Self {
-        Self::from_byte(nibble.as_half_byte())
+        Self::ALL[(nibble.as_half_byte() & 0xF) as usize]
     }
 
     pub fn to_nibble(self) -> Nibble {
         Nibble::new_from_half_byte(self as usize as u8)
     }
 
+    /// `V0` through `VF`, in order
+    pub const ALL: [Self; 16] = [
+        Self::V0,
+        Self::V1,
+        Self::V2,
+        Self::V3,
+        Self::V4,
+        Self::V5,
+        Self::V6,
+        Self::V7,
+        Self::V8,
+        Self::V9,
+        Self::VA,
+        Self::VB,
+        Self::VC,
+        Self::VD,
+        Self::VE,
+        Self::VF,
+    ];
+
+    /// Panics if `index` isn't a register, prefer `from_nibble` for unvalidated input
     pub fn from_byte(index: u8) -> Self {
         Self::from_byte_checked(index)
             .unwrap_or_else(|| panic!("Invalid index for register! {}", index))
     }
 
     fn from_byte_checked(index: u8) -> Option<Self> {
-        Some(match index {
-            0 => Self::V0,
-            1 => Self::V1,
-            2 => Self::V2,
-            3 => Self::V3,
-            4 => Self::V4,
-            5 => Self::V5,
-            6 => Self::V6,
-            7 => Self::V7,
-            8 => Self::V8,
-            9 => Self::V9,
-            10 => Self::VA,
-            11 => Self::VB,
-            12 => Self::VC,
-            13 => Self::VD,
-            14 => Self::VE,
-            15 => Self::VF,
-            _ => None?,
-        })
+        Self::ALL.get(index as usize).copied()
+    }
+
+    /// Every register, `V0` through `VF`
+    pub fn all() -> impl Iterator<Item = Self> {
+        Self::ALL.into_iter()
     }
 
     pub fn until_including(self) -> impl Iterator<Item = Self> {
         (0..=(self as usize as u8)).map(Self::from_byte)
     }
 
+    /// Parses `v0`..`vf`, in either case
     pub fn from_name(from: &str) -> Option<Self> {
-        if from.len() == 2 {
-            Self::from_byte_checked(u8::from_str_radix(&from[1..], 16).ok()?)
+        let index = from.strip_prefix(['v', 'V'])?;
+        if index.len() == 1 {
+            Self::from_byte_checked(u8::from_str_radix(index, 16).ok()?)
         } else {
             None
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_all_registers() {
+        let all = GeneralRegister::all().collect::<Vec<_>>();
+        assert_eq!(all.len(), 16);
+        assert_eq!(all, GeneralRegister::ALL);
+        assert!(all.iter().enumerate().all(|(i, reg)| reg.index() == i));
+        assert_eq!(all.last(), Some(&GeneralRegister::VF));
+        assert!(GeneralRegister::VF
+            .until_including()
+            .eq(GeneralRegister::all()));
+    }
+}
@@ -1,13 +1,48 @@
 use crate::asm::{FileLoadError, LoadError, ROM};
 use crate::{Address, Datum, NUMBER_OF_ADDRESSES};
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
 use std::io::Write;
-use std::ops::{Index, IndexMut};
+use std::ops::{Index, IndexMut, Range};
 use std::path::Path;
+use std::sync::Arc;
 use tap::TryConv;
+use thiserror::Error;
 
 #[derive(Debug, Clone)]
 #[allow(missing_copy_implementations)]
-pub struct Memory([Datum; NUMBER_OF_ADDRESSES]);
+pub struct Memory(
+    [Datum; NUMBER_OF_ADDRESSES],
+    Option<Box<MappedIo>>,
+    /// Addresses the program may not write to
+    Option<Range<Address>>,
+);
+
+/// The program tried to write into memory marked with [`Memory::protect`]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Error)]
+#[error("0x{0:03X} is write-protected")]
+pub struct WriteProtected(pub Address);
+
+/// Called with the stored value, and returns the value the program sees instead
+pub type ReadCallback = Arc<dyn Fn(Address, Datum) -> Datum + Send + Sync>;
+/// Called after the value has been stored
+pub type WriteCallback = Arc<dyn Fn(Address, Datum) + Send + Sync>;
+
+/// Addresses that dispatch to callbacks when the program reads or writes them
+#[derive(Clone, Default)]
+struct MappedIo {
+    reads: HashMap<Address, ReadCallback>,
+    writes: HashMap<Address, WriteCallback>,
+}
+
+impl Debug for MappedIo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MappedIo")
+            .field("reads", &self.reads.keys().collect::<Vec<_>>())
+            .field("writes", &self.writes.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
 
 impl Memory {
     pub fn from_rom(rom: ROM) -> Self {
@@ -21,13 +56,13 @@ impl Memory {
         let working_data = rom.into_data();
         let out_vec = internal_data
             .into_iter()
-            .chain(working_data.into_iter())
+            .chain(working_data)
             .collect::<Vec<_>>();
         let out_data = out_vec
             .try_conv::<[Datum; NUMBER_OF_ADDRESSES]>()
             .expect("ROM is constant size, extending with constant size!");
 
-        Self(out_data)
+        Self(out_data, None, None)
     }
 
     pub fn from_file(path: impl AsRef<Path>) -> Result<Self, FileLoadError> {
@@ -39,7 +74,7 @@ impl Memory {
         if bytes.len() == NUMBER_OF_ADDRESSES {
             let bytes = bytes.try_conv::<[u8; NUMBER_OF_ADDRESSES]>().unwrap();
             let data = bytes.map(Datum);
-            Ok(Self(data))
+            Ok(Self(data, None, None))
         } else {
             Err(LoadError::WrongSize {
                 size: bytes.len(),
@@ -53,7 +88,59 @@ impl Memory {
         // Add an illegal instruction at the entrypoint
         inner[Address::PROGRAM_START.as_u16() as usize] = Datum(0x00);
         inner[Address::PROGRAM_START.as_u16() as usize + 1] = Datum(0xF0);
-        Self(inner)
+        Self(inner, None, None)
+    }
+
+    /// A read by the running program, which may be intercepted by `on_read`
+    pub fn read(&self, addr: Address) -> Datum {
+        let datum = self[addr];
+        match self.1.as_ref().and_then(|io| io.reads.get(&addr)) {
+            Some(callback) => callback(addr, datum),
+            None => datum,
+        }
+    }
+
+    /// A write by the running program, which may be observed by `on_write`.
+    /// Writes into the protected region are refused and leave memory unchanged.
+    pub fn write(&mut self, addr: Address, datum: Datum) -> Result<(), WriteProtected> {
+        if self.is_protected(addr) {
+            return Err(WriteProtected(addr));
+        }
+        self[addr] = datum;
+        if let Some(callback) = self.1.as_ref().and_then(|io| io.writes.get(&addr)) {
+            callback(addr, datum);
+        }
+        Ok(())
+    }
+
+    /// Refuses writes by the program to `region`, such as its own code, to catch programs
+    /// modifying themselves by mistake. Indexing is unaffected.
+    pub fn protect(&mut self, region: Range<Address>) {
+        self.2 = Some(region);
+    }
+
+    pub fn is_protected(&self, addr: Address) -> bool {
+        self.2.as_ref().is_some_and(|region| region.contains(&addr))
+    }
+
+    pub fn on_read(
+        &mut self,
+        addr: Address,
+        callback: impl Fn(Address, Datum) -> Datum + Send + Sync + 'static,
+    ) {
+        self.mapped_io().reads.insert(addr, Arc::new(callback));
+    }
+
+    pub fn on_write(
+        &mut self,
+        addr: Address,
+        callback: impl Fn(Address, Datum) + Send + Sync + 'static,
+    ) {
+        self.mapped_io().writes.insert(addr, Arc::new(callback));
+    }
+
+    fn mapped_io(&mut self) -> &mut MappedIo {
+        self.1.get_or_insert_with(Default::default)
     }
 
     pub fn substring(&self, start: Address, number: u8) -> &[Datum] {
@@ -66,6 +153,11 @@ impl Memory {
         &self.0[..]
     }
 
+    /// Every address where the two images differ, with this value then the other, in address order
+    pub fn diff(&self, other: &Self) -> Vec<(Address, Datum, Datum)> {
+        diff_data(&self.0, &other.0)
+    }
+
     pub(crate) fn extract(self) -> [Datum; NUMBER_OF_ADDRESSES] {
         self.0
     }
@@ -95,6 +187,15 @@ impl IndexMut<Address> for Memory {
     }
 }
 
+pub(crate) fn diff_data(left: &[Datum], right: &[Datum]) -> Vec<(Address, Datum, Datum)> {
+    left.iter()
+        .zip(right)
+        .enumerate()
+        .filter(|(_, (l, r))| l != r)
+        .map(|(i, (l, r))| (Address::new(i as u16), *l, *r))
+        .collect()
+}
+
 pub const FONT_START_ADDR: usize = 0x50;
 const FONT_DATA: [u8; 80] = [
     0xF0, 0x90, 0x90, 0x90, 0xF0, // 0
@@ -114,3 +215,41 @@ const FONT_DATA: [u8; 80] = [
     0xF0, 0x80, 0xF0, 0x80, 0xF0, // E
     0xF0, 0x80, 0xF0, 0x80, 0x80, // F
 ];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_protect() {
+        let mut memory = ROM::new().to_memory();
+        memory.protect(Address::PROGRAM_START..Address::new(0x300));
+
+        assert_eq!(
+            memory.write(Address::new(0x2FF), Datum(1)),
+            Err(WriteProtected(Address::new(0x2FF)))
+        );
+        assert_eq!(memory[Address::new(0x2FF)], Datum(0));
+        assert_eq!(memory.write(Address::new(0x300), Datum(1)), Ok(()));
+        assert_eq!(memory[Address::new(0x300)], Datum(1));
+    }
+
+    #[test]
+    fn test_diff() {
+        let original = ROM::new().to_memory();
+        let mut changed = original.clone();
+        for (addr, datum) in [(0x300, 0x12), (0x050, 0x00), (0xFFF, 0xAB)] {
+            changed[Address::new(addr)] = Datum(datum);
+        }
+
+        assert_eq!(
+            original.diff(&changed),
+            vec![
+                (Address::new(0x050), Datum(0xF0), Datum(0x00)),
+                (Address::new(0x300), Datum(0x00), Datum(0x12)),
+                (Address::new(0xFFF), Datum(0x00), Datum(0xAB)),
+            ]
+        );
+        assert!(original.diff(&original.clone()).is_empty());
+    }
+}
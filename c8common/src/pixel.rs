@@ -3,3 +3,29 @@ pub enum Pixel {
     Black,
     White,
 }
+
+impl Pixel {
+    pub fn inverted(self) -> Self {
+        match self {
+            Self::Black => Self::White,
+            Self::White => Self::Black,
+        }
+    }
+}
+
+/// `chip8_base` and most frontends treat a lit pixel as `true`
+impl From<bool> for Pixel {
+    fn from(lit: bool) -> Self {
+        if lit {
+            Self::White
+        } else {
+            Self::Black
+        }
+    }
+}
+
+impl From<Pixel> for bool {
+    fn from(value: Pixel) -> Self {
+        value == Pixel::White
+    }
+}
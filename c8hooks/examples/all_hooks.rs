@@ -0,0 +1,45 @@
+//! Runs a short program with the execution dumper, recorder and tracer all attached,
+//! as the runner does for `--dump`, `--record` and `--trace`
+//!
+//! `cargo run -p c8hooks --example all_hooks`
+
+use c8common::asm::ROM;
+use c8common::control::execute::Interpreter;
+use c8common::key::Keys;
+use c8common::{Address, GeneralRegister, Instruction};
+use c8hooks::execution_dumper::ExecutionDumper;
+use c8hooks::recorder::Recorder;
+use c8hooks::tracer::Tracer;
+use c8int::Chip8Interpreter;
+
+fn main() {
+    let rom = ROM::from_instructions(&[
+        Instruction::LoadRegByte(GeneralRegister::V0, 0),
+        Instruction::GetSprite(GeneralRegister::V0),
+        Instruction::DisplaySprite {
+            x: GeneralRegister::V0,
+            y: GeneralRegister::V0,
+            number_of_bytes: 5,
+        },
+        Instruction::Add(GeneralRegister::V0, 1),
+        Instruction::Jump(Address::new(0x202)),
+    ])
+    .expect("the program should fit in memory");
+
+    let output = std::env::temp_dir();
+    let (dump, record) = (
+        output.join("c8hooks_example.dump"),
+        output.join("c8hooks_example.json"),
+    );
+    let mut interpreter = Interpreter::builder()
+        .extend_with(ExecutionDumper::dump_to(&dump).expect("could not create dump file"))
+        .extend_with(Recorder::compact(&record))
+        .extend_with(Tracer::new().with_max_lines(8))
+        .build(Chip8Interpreter::new_from_rom(rom));
+
+    for _ in 0..16 {
+        interpreter.step(Keys::from_raw([false; 16]));
+    }
+    println!("Dumped to {}", dump.display());
+    println!("Recorded to {}", record.display());
+}
@@ -0,0 +1,271 @@
+use c8common::control::{ControlledInterpreter, FrameInfo};
+use c8common::hooks::{HookInternalAccess, InterpreterHook};
+use c8common::{Address, GeneralRegister};
+use std::fmt::Debug;
+use std::fs::File;
+use std::io::{BufWriter, ErrorKind, Read, Write};
+use std::path::Path;
+
+/// Size in bytes of one record in a binary trace
+pub const RECORD_SIZE: usize = 14;
+
+const NO_REGISTER: u8 = 0xFF;
+
+/// One entry of a binary trace: an executed instruction and, if any, a register it changed.
+///
+/// An instruction that changes several registers is written as one record per register, all
+/// sharing the same cycle.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct TraceRecord {
+    pub cycle: u64,
+    pub pc: Address,
+    pub opcode: u16,
+    pub changed: Option<(GeneralRegister, u8)>,
+}
+
+impl TraceRecord {
+    /// Big-endian cycle, pc and opcode, then the register index (`0xFF` for none) and its value
+    pub fn to_bytes(&self) -> [u8; RECORD_SIZE] {
+        let mut bytes = [0; RECORD_SIZE];
+        bytes[0..8].copy_from_slice(&self.cycle.to_be_bytes());
+        bytes[8..10].copy_from_slice(&self.pc.as_u16().to_be_bytes());
+        bytes[10..12].copy_from_slice(&self.opcode.to_be_bytes());
+        let (register, value) = self
+            .changed
+            .map(|(register, value)| (register.index() as u8, value))
+            .unwrap_or((NO_REGISTER, 0));
+        bytes[12] = register;
+        bytes[13] = value;
+        bytes
+    }
+
+    /// `None` if the pc is past the end of memory or the register index isn't one, as in a
+    /// corrupt trace or some other file
+    pub fn from_bytes(bytes: [u8; RECORD_SIZE]) -> Option<Self> {
+        let mut cycle = [0; 8];
+        cycle.copy_from_slice(&bytes[0..8]);
+        let changed = match bytes[12] {
+            NO_REGISTER => None,
+            index => Some((*GeneralRegister::ALL.get(index as usize)?, bytes[13])),
+        };
+        Some(Self {
+            cycle: u64::from_be_bytes(cycle),
+            pc: Address::try_from(u16::from_be_bytes([bytes[8], bytes[9]])).ok()?,
+            opcode: u16::from_be_bytes([bytes[10], bytes[11]]),
+            changed,
+        })
+    }
+}
+
+/// Writes a compact binary trace of every executed instruction, see [`TraceRecord`]
+#[derive(Debug)]
+pub struct BinaryTracer<W: Write = BufWriter<File>> {
+    to: W,
+    cycle: u64,
+    pc: Option<Address>,
+}
+
+impl BinaryTracer {
+    pub fn trace_to(to: impl AsRef<Path>) -> Result<Self, std::io::Error> {
+        Ok(Self::new(BufWriter::new(File::create(to)?)))
+    }
+}
+
+impl<W: Write> BinaryTracer<W> {
+    pub fn new(to: W) -> Self {
+        Self {
+            to,
+            cycle: 0,
+            pc: None,
+        }
+    }
+
+    fn write(&mut self, record: TraceRecord) {
+        self.to.write_all(&record.to_bytes()).unwrap();
+    }
+}
+
+impl<T: ControlledInterpreter, W: Write + Debug + Send> InterpreterHook<T> for BinaryTracer<W> {
+    fn before_step(&mut self, int: &mut T, _: &mut FrameInfo) {
+        self.pc = Some(int.program_counter());
+    }
+
+    fn after_step(&mut self, int: &mut T, frame: &mut FrameInfo) {
+        let (Some(executed), Some(pc)) = (frame.executed(), self.pc.take()) else {
+            return;
+        };
+        let opcode = executed.to_data().to_u16();
+        let changed = <Self as HookInternalAccess<T>>::changed_registers(&*self, &*frame);
+        let mut record = TraceRecord {
+            cycle: self.cycle,
+            pc,
+            opcode,
+            changed: None,
+        };
+        if changed == 0 {
+            self.write(record);
+        }
+        for index in (0..16).filter(|index| changed & (1 << index) != 0) {
+            let register = GeneralRegister::from_byte(index);
+            record.changed = Some((register, int.get_register(register).0));
+            self.write(record);
+        }
+        self.cycle += 1;
+    }
+}
+
+/// Reads the records of a binary trace back, stopping cleanly at the end of the input.
+/// A record that can't be one fails with `ErrorKind::InvalidData`.
+#[derive(Debug)]
+pub struct BinaryTraceReader<R: Read> {
+    from: R,
+}
+
+impl<R: Read> BinaryTraceReader<R> {
+    pub fn new(from: R) -> Self {
+        Self { from }
+    }
+}
+
+impl<R: Read> Iterator for BinaryTraceReader<R> {
+    type Item = Result<TraceRecord, std::io::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut bytes = [0; RECORD_SIZE];
+        match self.from.read_exact(&mut bytes) {
+            Ok(()) => Some(TraceRecord::from_bytes(bytes).ok_or_else(|| {
+                std::io::Error::new(ErrorKind::InvalidData, "not a binary trace record")
+            })),
+            Err(err) if err.kind() == ErrorKind::UnexpectedEof => None,
+            Err(err) => Some(Err(err)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use c8common::asm::ROM;
+    use c8common::control::execute::Interpreter;
+    use c8common::key::Keys;
+    use c8common::Instruction;
+    use c8int::Chip8Interpreter;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_round_trip() {
+        let records = [
+            TraceRecord {
+                cycle: 0,
+                pc: Address::new(0x200),
+                opcode: 0x6005,
+                changed: Some((GeneralRegister::V0, 5)),
+            },
+            TraceRecord {
+                cycle: 1,
+                pc: Address::new(0x202),
+                opcode: 0x00E0,
+                changed: None,
+            },
+            TraceRecord {
+                cycle: 2,
+                pc: Address::new(0x204),
+                opcode: 0x8014,
+                changed: Some((GeneralRegister::VF, 1)),
+            },
+        ];
+        let mut trace = vec![];
+        for record in &records {
+            trace.extend(record.to_bytes());
+        }
+        assert_eq!(trace.len(), records.len() * RECORD_SIZE);
+
+        let read = BinaryTraceReader::new(trace.as_slice())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(read, records);
+    }
+
+    #[test]
+    fn test_corrupt_record() {
+        let mut trace = TraceRecord {
+            cycle: 0,
+            pc: Address::new(0x200),
+            opcode: 0x00E0,
+            changed: None,
+        }
+        .to_bytes()
+        .to_vec();
+        let mut bad_register = trace.clone();
+        bad_register[12] = 16;
+        let mut bad_pc = trace.clone();
+        bad_pc[8] = 0x10;
+        trace.extend(bad_register);
+        trace.extend(bad_pc);
+
+        let read = BinaryTraceReader::new(trace.as_slice()).collect::<Vec<_>>();
+        assert_eq!(read.len(), 3);
+        assert!(read[0].is_ok());
+        for record in &read[1..] {
+            assert_eq!(record.as_ref().unwrap_err().kind(), ErrorKind::InvalidData);
+        }
+    }
+
+    #[derive(Debug, Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_traces_execution() {
+        let rom = ROM::from_instructions(&[
+            Instruction::LoadRegByte(GeneralRegister::V0, 5),
+            Instruction::ClearScreen,
+            Instruction::LoadRegByte(GeneralRegister::V1, 7),
+        ])
+        .unwrap();
+        let buffer = SharedBuffer::default();
+        let mut int = Interpreter::builder()
+            .extend_with(BinaryTracer::new(buffer.clone()))
+            .build(Chip8Interpreter::new_from_rom(rom));
+        for _ in 0..3 {
+            int.step(Keys::from_raw([false; 16]));
+        }
+
+        let trace = buffer.0.lock().unwrap();
+        let read = BinaryTraceReader::new(trace.as_slice())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(
+            read,
+            [
+                TraceRecord {
+                    cycle: 0,
+                    pc: Address::new(0x200),
+                    opcode: 0x6005,
+                    changed: Some((GeneralRegister::V0, 5)),
+                },
+                TraceRecord {
+                    cycle: 1,
+                    pc: Address::new(0x202),
+                    opcode: 0x00E0,
+                    changed: None,
+                },
+                TraceRecord {
+                    cycle: 2,
+                    pc: Address::new(0x204),
+                    opcode: 0x6107,
+                    changed: Some((GeneralRegister::V1, 7)),
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,154 @@
+use c8common::control::{ControlledInterpreter, FrameInfo, InterpreterState};
+use c8common::hooks::InterpreterHook;
+use c8common::{Address, Instruction};
+use log::info;
+use std::fmt::{Debug, Formatter};
+
+/// Holds the interpreter when execution reaches an address or executes a matching instruction
+/// Use `Interpreter::resume` to continue afterwards
+#[derive(Debug, Default)]
+pub struct Breakpoint {
+    addresses: Vec<Address>,
+    instructions: Vec<InstructionBreakpoint>,
+    hit: Option<BreakReason>,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum BreakReason {
+    /// The program counter reached this address; the instruction there has not run yet
+    Address(Address),
+    /// This instruction matched a predicate, and has already run
+    Instruction(Instruction),
+}
+
+struct InstructionBreakpoint(Box<dyn Fn(&Instruction) -> bool + Send>);
+
+impl Debug for InstructionBreakpoint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("InstructionBreakpoint")
+    }
+}
+
+impl Breakpoint {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_address(mut self, addr: Address) -> Self {
+        self.addresses.push(addr);
+        self
+    }
+
+    pub fn with_instruction(
+        mut self,
+        predicate: impl Fn(&Instruction) -> bool + Send + 'static,
+    ) -> Self {
+        self.instructions
+            .push(InstructionBreakpoint(Box::new(predicate)));
+        self
+    }
+
+    /// Why the most recent break happened
+    pub fn last_hit(&self) -> Option<BreakReason> {
+        self.hit
+    }
+}
+
+impl<T: ControlledInterpreter> InterpreterHook<T> for Breakpoint {
+    fn pre_cycle(&mut self, _: &mut InterpreterState) {
+        self.hit = None;
+    }
+
+    fn after_step(&mut self, int: &mut T, frame: &mut FrameInfo) {
+        if let Some(executed) = frame.executed() {
+            if self
+                .instructions
+                .iter()
+                .any(|matches| (matches.0)(&executed))
+            {
+                self.hit = Some(BreakReason::Instruction(executed));
+                return;
+            }
+        }
+        let pc = int.program_counter();
+        if self.addresses.contains(&pc) {
+            self.hit = Some(BreakReason::Address(pc));
+        }
+    }
+
+    fn post_cycle(&mut self, state: &mut InterpreterState) {
+        if let Some(reason) = self.hit {
+            info!("Breakpoint hit: {:?}", reason);
+            *state = InterpreterState::Held;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use c8common::asm::ROM;
+    use c8common::control::execute::Interpreter;
+    use c8common::key::Keys;
+    use c8common::GeneralRegister;
+    use c8int::Chip8Interpreter;
+
+    #[test]
+    fn test_break_on_first_clear_screen() {
+        let rom = ROM::from_instructions(&[
+            Instruction::LoadRegByte(GeneralRegister::V0, 1),
+            Instruction::Add(GeneralRegister::V0, 1),
+            Instruction::ClearScreen,
+            Instruction::ClearScreen,
+            Instruction::Jump(Address::new(0x208)),
+        ])
+        .unwrap();
+        let mut int = Interpreter::builder()
+            .extend_with(Breakpoint::new().with_instruction(|i| *i == Instruction::ClearScreen))
+            .build(Chip8Interpreter::new_from_rom(rom));
+
+        let keys = Keys::from_raw([false; 16]);
+        for _ in 0..2 {
+            int.step(keys);
+            assert_eq!(*int.state(), InterpreterState::Normal);
+        }
+        int.step(keys);
+        assert_eq!(*int.state(), InterpreterState::Held);
+        assert_eq!(int.inner().program_counter(), Address::new(0x206));
+
+        assert!(int.step(keys).is_none());
+        assert_eq!(int.inner().program_counter(), Address::new(0x206));
+
+        int.resume();
+        int.step(keys);
+        assert_eq!(*int.state(), InterpreterState::Held);
+        assert_eq!(int.inner().program_counter(), Address::new(0x208));
+    }
+
+    #[test]
+    fn test_break_during_key_wait() {
+        let rom = ROM::from_instructions(&[
+            Instruction::WaitForKey(GeneralRegister::V0),
+            Instruction::Add(GeneralRegister::V1, 1),
+            Instruction::Jump(Address::new(0x202)),
+        ])
+        .unwrap();
+        let mut int = Interpreter::builder()
+            .extend_with(
+                Breakpoint::new().with_instruction(|i| matches!(i, Instruction::WaitForKey(_))),
+            )
+            .build(Chip8Interpreter::new_from_rom(rom));
+
+        int.step(Keys::from_raw([false; 16]));
+        assert_eq!(*int.state(), InterpreterState::Held);
+
+        int.resume();
+        assert_eq!(
+            *int.state(),
+            InterpreterState::WaitForKey(GeneralRegister::V0)
+        );
+        int.step(Keys::from_number(0x5));
+        assert_eq!(*int.state(), InterpreterState::Normal);
+        assert_eq!(int.inner().get_register(GeneralRegister::V0).0, 0x5);
+    }
+}
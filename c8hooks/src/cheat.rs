@@ -0,0 +1,130 @@
+use c8common::control::{ControlledInterpreter, FrameInfo};
+use c8common::hooks::InterpreterHook;
+use c8common::{Address, Datum, GeneralRegister};
+use std::fmt::{Debug, Formatter};
+
+/// Overwrites memory and registers immediately before every step, like a trainer
+#[derive(Debug, Default)]
+pub struct Cheat {
+    pokes: Vec<Poke>,
+    condition: Option<Condition>,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Poke {
+    Memory(Address, Datum),
+    Register(GeneralRegister, Datum),
+}
+
+struct Condition(Box<dyn Fn(Address) -> bool + Send>);
+
+impl Debug for Condition {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Condition")
+    }
+}
+
+impl Cheat {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn poke(mut self, addr: Address, datum: Datum) -> Self {
+        self.pokes.push(Poke::Memory(addr, datum));
+        self
+    }
+
+    pub fn force_register(mut self, register: GeneralRegister, datum: Datum) -> Self {
+        self.pokes.push(Poke::Register(register, datum));
+        self
+    }
+
+    /// Only apply the pokes when the program counter matches
+    pub fn when(mut self, condition: impl Fn(Address) -> bool + Send + 'static) -> Self {
+        self.condition = Some(Condition(Box::new(condition)));
+        self
+    }
+
+    pub fn pokes(&self) -> &[Poke] {
+        &self.pokes
+    }
+
+    fn apply<T: ControlledInterpreter>(&self, int: &mut T) {
+        for poke in &self.pokes {
+            match *poke {
+                Poke::Memory(addr, datum) => int.memory_mut()[addr] = datum,
+                Poke::Register(register, datum) => int.set_register(register, datum),
+            }
+        }
+    }
+}
+
+impl<T: ControlledInterpreter> InterpreterHook<T> for Cheat {
+    fn before_step(&mut self, int: &mut T, _: &mut FrameInfo) {
+        let active = self
+            .condition
+            .as_ref()
+            .map(|condition| (condition.0)(int.program_counter()))
+            .unwrap_or(true);
+        if active {
+            self.apply(int);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use c8common::asm::ROM;
+    use c8common::control::execute::Interpreter;
+    use c8common::key::Keys;
+    use c8common::Instruction;
+    use c8int::Chip8Interpreter;
+
+    #[test]
+    fn test_forced_register_sticks() {
+        let rom = ROM::from_instructions(&[
+            Instruction::LoadRegByte(GeneralRegister::V1, 9),
+            Instruction::AddReg {
+                x: GeneralRegister::V0,
+                y: GeneralRegister::V1,
+            },
+            Instruction::Jump(Address::new(0x202)),
+        ])
+        .unwrap();
+        let mut int = Interpreter::builder()
+            .extend_with(Cheat::new().force_register(GeneralRegister::V1, Datum(5)))
+            .build(Chip8Interpreter::new_from_rom(rom));
+
+        let keys = Keys::from_raw([false; 16]);
+        for _ in 0..7 {
+            int.step(keys);
+        }
+        assert_eq!(int.inner().get_register(GeneralRegister::V1), Datum(5));
+        assert_eq!(int.inner().get_register(GeneralRegister::V0), Datum(15));
+    }
+
+    #[test]
+    fn test_conditional_poke() {
+        let rom = ROM::from_instructions(&[
+            Instruction::Nop,
+            Instruction::Nop,
+            Instruction::Jump(Address::new(0x200)),
+        ])
+        .unwrap();
+        let target = Address::new(0x300);
+        let mut int = Interpreter::builder()
+            .extend_with(
+                Cheat::new()
+                    .poke(target, Datum(0xAB))
+                    .when(|pc| pc == Address::new(0x202)),
+            )
+            .build(Chip8Interpreter::new_from_rom(rom));
+
+        let keys = Keys::from_raw([false; 16]);
+        int.step(keys);
+        assert_eq!(int.inner().memory()[target], Datum(0));
+        int.step(keys);
+        assert_eq!(int.inner().memory()[target], Datum(0xAB));
+    }
+}
@@ -0,0 +1,112 @@
+use c8common::control::{ControlledInterpreter, FrameInfo};
+use c8common::hooks::InterpreterHook;
+use c8common::{Address, Instruction};
+use std::collections::BTreeSet;
+
+/// Records which kinds of instruction, and at which addresses, a program executes, to measure
+/// how much of the instruction set a test ROM exercises
+#[derive(Debug, Default)]
+pub struct Coverage {
+    variants: BTreeSet<&'static str>,
+    addresses: BTreeSet<Address>,
+    pc: Option<Address>,
+}
+
+/// Instruction variants by name, such as `AddReg`, each in alphabetical order
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct CoverageReport {
+    pub covered: Vec<&'static str>,
+    pub uncovered: Vec<&'static str>,
+}
+
+impl Coverage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Every address an instruction was executed from
+    pub fn addresses(&self) -> &BTreeSet<Address> {
+        &self.addresses
+    }
+
+    pub fn report(&self) -> CoverageReport {
+        let mut all = Instruction::VARIANT_NAMES;
+        all.sort_unstable();
+        let (covered, uncovered) = all
+            .into_iter()
+            .partition(|variant| self.variants.contains(variant));
+        CoverageReport { covered, uncovered }
+    }
+}
+
+impl<T: ControlledInterpreter> InterpreterHook<T> for Coverage {
+    fn before_step(&mut self, int: &mut T, _: &mut FrameInfo) {
+        self.pc = Some(int.program_counter());
+    }
+
+    fn after_step(&mut self, _: &mut T, frame: &mut FrameInfo) {
+        if let (Some(executed), Some(pc)) = (frame.executed(), self.pc.take()) {
+            self.variants.insert(executed.variant_name());
+            self.addresses.insert(pc);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use c8common::asm::ROM;
+    use c8common::control::execute::Interpreter;
+    use c8common::key::Keys;
+    use c8common::GeneralRegister;
+    use c8int::Chip8Interpreter;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Debug)]
+    struct Shared(Arc<Mutex<Coverage>>);
+
+    impl<T: ControlledInterpreter> InterpreterHook<T> for Shared {
+        fn before_step(&mut self, int: &mut T, frame: &mut FrameInfo) {
+            self.0.lock().unwrap().before_step(int, frame)
+        }
+
+        fn after_step(&mut self, int: &mut T, frame: &mut FrameInfo) {
+            self.0.lock().unwrap().after_step(int, frame)
+        }
+    }
+
+    #[test]
+    fn test_covered_variants() {
+        let rom = ROM::from_instructions(&[
+            Instruction::LoadRegByte(GeneralRegister::V0, 1),
+            Instruction::Add(GeneralRegister::V0, 1),
+            Instruction::AddReg {
+                x: GeneralRegister::V1,
+                y: GeneralRegister::V0,
+            },
+            Instruction::Jump(Address::new(0x206)),
+        ])
+        .unwrap();
+        let coverage = Arc::new(Mutex::new(Coverage::new()));
+        let mut int = Interpreter::builder()
+            .extend_with(Shared(coverage.clone()))
+            .build(Chip8Interpreter::new_from_rom(rom));
+        for _ in 0..4 {
+            int.step(Keys::from_raw([false; 16]));
+        }
+
+        let coverage = coverage.lock().unwrap();
+        let report = coverage.report();
+        assert_eq!(report.covered, ["Add", "AddReg", "Jump", "LoadRegByte"]);
+        assert!(report.uncovered.contains(&"ClearScreen"));
+        assert!(!report.uncovered.contains(&"Add"));
+        assert_eq!(
+            report.covered.len() + report.uncovered.len(),
+            Instruction::VARIANT_NAMES.len()
+        );
+        assert_eq!(
+            coverage.addresses().iter().copied().collect::<Vec<_>>(),
+            [0x200, 0x202, 0x204, 0x206].map(Address::new)
+        );
+    }
+}
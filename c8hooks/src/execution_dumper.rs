@@ -70,6 +70,9 @@ impl<T: ControlledInterpreter> InterpreterHook<T> for ExecutionDumper {
         if let Some(reg) = <Self as HookInternalAccess<T>>::is_wait_for_key(&*self, &*frame) {
             self.dump(format!("> Waiting to store next keypress in {:?}", reg));
         }
+        if <Self as HookInternalAccess<T>>::is_halted(&*self, &*frame) {
+            self.dump("> Halted".to_string());
+        }
     }
 
     fn post_cycle(&mut self, state: &mut InterpreterState) {
@@ -95,7 +98,7 @@ impl ExecutionDumper {
             "> Program counter = {:03X}",
             state.program_counter().as_u16()
         ));
-        self.dump("> Stack:".to_string());
+        self.dump(format!("> Stack ({} deep):", state.stack_depth()));
         for (i, addr) in state.stack().iter().enumerate() {
             self.dump(format!(">> {}: {:03X}", i, addr.as_u16()));
         }
@@ -1,5 +1,10 @@
 #![deny(missing_debug_implementations, unused_must_use)]
 #![warn(missing_copy_implementations)]
 
+pub mod binary_tracer;
+pub mod breakpoint;
+pub mod cheat;
+pub mod coverage;
 pub mod execution_dumper;
 pub mod recorder;
+pub mod tracer;
@@ -1,7 +1,9 @@
 use c8common::control::{ControlledInterpreter, FrameInfo, InterpreterState};
+use c8common::display::Frontend;
 use c8common::hooks::{HookInternalAccess, InterpreterHook};
 use c8common::Display;
 use image::{GrayImage, Luma};
+use std::convert::Infallible;
 use std::fs::File;
 use std::io::Write;
 use std::path::PathBuf;
@@ -12,6 +14,8 @@ pub struct Recorder {
     step_number: u64,
     frame_number: u64,
     mode: RecorderMode,
+    inverted: bool,
+    fade: Option<Phosphor>,
 }
 
 impl Recorder {
@@ -22,6 +26,8 @@ impl Recorder {
             step_number: 0,
             frame_number: 0,
             mode: RecorderMode::Images { folder: path },
+            inverted: false,
+            fade: None,
         }
     }
 
@@ -31,10 +37,25 @@ impl Recorder {
             step_number: 0,
             frame_number: 0,
             mode: RecorderMode::Compact,
+            inverted: false,
+            fade: None,
         }
     }
 
-    fn write_new_frame(&mut self, frame: Display) {
+    /// Save images as black-on-white
+    pub fn with_inverted(mut self, inverted: bool) -> Self {
+        self.inverted = inverted;
+        self
+    }
+
+    /// Let cleared pixels decay in saved images instead of switching straight off, keeping
+    /// `factor` of their brightness each frame. Compact recordings are always 1-bit.
+    pub fn with_fade(mut self, factor: f32) -> Self {
+        self.fade = Some(Phosphor::new(factor));
+        self
+    }
+
+    fn write_new_frame(&mut self, frame: &Display) {
         match self.mode {
             RecorderMode::Images { ref folder } => {
                 let new_image_path = folder.join(format!("{}.png", self.frame_number));
@@ -47,18 +68,15 @@ impl Recorder {
                 )
                 .unwrap();
                 self.close();
-                let mut image = GrayImage::new(64, 32);
-                for (y, row) in frame.raw().iter().enumerate() {
-                    for (x, &pixel) in row.iter().enumerate() {
-                        image.put_pixel(
-                            x as u32,
-                            y as u32,
-                            if pixel as usize == 1 {
-                                Luma([255])
-                            } else {
-                                Luma([0])
-                            },
-                        )
+                let brightness = match self.fade {
+                    Some(ref mut phosphor) => phosphor.apply(frame),
+                    None => Phosphor::new(0.).apply(frame),
+                };
+                let mut image = GrayImage::new(frame.width() as u32, frame.height() as u32);
+                for (y, row) in brightness.iter().enumerate() {
+                    for (x, &level) in row.iter().enumerate() {
+                        let level = if self.inverted { 255 - level } else { level };
+                        image.put_pixel(x as u32, y as u32, Luma([level]))
                     }
                 }
                 image.save(new_image_path).unwrap();
@@ -107,7 +125,7 @@ impl<T: ControlledInterpreter> InterpreterHook<T> for Recorder {
         if self.frame_number == 0
             || <Self as HookInternalAccess<T>>::is_modify_screen(&*self, frame)
         {
-            self.write_new_frame(*int.display())
+            self.write_new_frame(int.display())
         }
     }
 
@@ -116,8 +134,91 @@ impl<T: ControlledInterpreter> InterpreterHook<T> for Recorder {
     }
 }
 
+impl Frontend for Recorder {
+    type Error = Infallible;
+
+    fn present(&mut self, frame: &Display) -> Result<(), Self::Error> {
+        self.write_new_frame(frame);
+        Ok(())
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum RecorderMode {
     Images { folder: PathBuf },
     Compact,
 }
+
+/// How brightly each pixel glows, for fading pixels out over several frames
+#[derive(Debug, Clone)]
+struct Phosphor {
+    factor: f32,
+    brightness: Vec<Vec<u8>>,
+}
+
+impl Phosphor {
+    fn new(factor: f32) -> Self {
+        Self {
+            factor: factor.clamp(0., 1.),
+            brightness: Vec::new(),
+        }
+    }
+
+    /// Lit pixels are at full brightness, and the rest keep `factor` of what they had.
+    /// Changing resolution clears the screen, so then everything starts dark again.
+    fn apply(&mut self, frame: &Display) -> Vec<Vec<u8>> {
+        if self.brightness.len() != frame.height() || self.brightness[0].len() != frame.width() {
+            self.brightness = vec![vec![0; frame.width()]; frame.height()];
+        }
+        for (levels, row) in self.brightness.iter_mut().zip(frame.raw()) {
+            for (level, &pixel) in levels.iter_mut().zip(row) {
+                *level = if bool::from(pixel) {
+                    255
+                } else {
+                    (*level as f32 * self.factor) as u8
+                };
+            }
+        }
+        self.brightness.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use c8common::pixel::Pixel;
+    use c8common::Datum;
+
+    #[test]
+    fn test_fade_leaves_cleared_pixels_gray() {
+        let mut phosphor = Phosphor::new(0.5);
+        let mut display = Display::blank();
+        let _ = display.sprite(Datum(0), Datum(0), &[Datum(0b1000_0000)]);
+        assert_eq!(phosphor.apply(&display)[0][0], 255);
+
+        display.clear();
+        assert_eq!(phosphor.apply(&display)[0][0], 127);
+        assert_eq!(phosphor.apply(&display)[0][0], 63);
+        assert_eq!(phosphor.apply(&display)[0][1], 0);
+
+        assert_eq!(Phosphor::new(0.).apply(&display)[0][0], 0);
+    }
+
+    #[test]
+    fn test_records_hires_frames_at_full_size() {
+        let folder =
+            std::env::temp_dir().join(format!("c8hooks_recorder_hires_{}", std::process::id()));
+        std::fs::create_dir_all(&folder).unwrap();
+        let mut recorder = Recorder::images_to_folder(&folder).with_fade(0.5);
+        let mut display = Display::blank();
+        display.set_hires(true);
+        display.set_pixel(127, 63, Pixel::White);
+        recorder.present(&display).unwrap();
+
+        let image = image::open(folder.join("0.png")).unwrap().into_luma8();
+        assert_eq!(image.dimensions(), (128, 64));
+        assert_eq!(image.get_pixel(127, 63), &Luma([255]));
+        assert_eq!(image.get_pixel(0, 0), &Luma([0]));
+        std::fs::remove_dir_all(&folder).unwrap();
+    }
+}
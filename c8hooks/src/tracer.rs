@@ -0,0 +1,123 @@
+use c8common::control::{ControlledInterpreter, FrameInfo};
+use c8common::hooks::InterpreterHook;
+use c8common::{Address, Datum, GeneralRegister};
+use log::trace;
+
+/// Logs one line per executed instruction at `trace` level, without the overhead of a file
+#[derive(Debug, Default)]
+#[allow(missing_copy_implementations)]
+pub struct Tracer {
+    max_lines: Option<u64>,
+    lines: u64,
+    before: Option<(Address, [Datum; 16])>,
+}
+
+impl Tracer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stop tracing after this many instructions
+    pub fn with_max_lines(mut self, max_lines: u64) -> Self {
+        self.max_lines = Some(max_lines);
+        self
+    }
+
+    pub fn lines_written(&self) -> u64 {
+        self.lines
+    }
+
+    fn exhausted(&self) -> bool {
+        self.max_lines.map(|max| self.lines >= max).unwrap_or(false)
+    }
+}
+
+impl<T: ControlledInterpreter> InterpreterHook<T> for Tracer {
+    fn before_step(&mut self, int: &mut T, _: &mut FrameInfo) {
+        self.before = Some((
+            int.program_counter(),
+            int.register_bank().map(|datum| *datum),
+        ));
+    }
+
+    fn after_step(&mut self, int: &mut T, frame: &mut FrameInfo) {
+        let (Some(executed), Some((pc, registers))) = (frame.executed(), self.before.take()) else {
+            return;
+        };
+        if self.exhausted() {
+            return;
+        }
+        let raw = executed.to_data();
+        let mut line = format!(
+            "{:03X}: {:04X} {}",
+            pc.as_u16(),
+            raw.to_u16(),
+            executed.mnemonic()
+        );
+        for (register, (before, after)) in
+            GeneralRegister::all().zip(registers.iter().zip(int.register_bank()))
+        {
+            if before != after {
+                line.push_str(&format!(" {:?}={:02X}", register, after));
+            }
+        }
+        trace!("{}", line);
+        self.lines += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use c8common::asm::ROM;
+    use c8common::control::execute::Interpreter;
+    use c8common::key::Keys;
+    use c8common::Instruction;
+    use c8int::Chip8Interpreter;
+    use log::{Level, LevelFilter, Log, Metadata, Record};
+    use std::sync::Mutex;
+
+    static CAPTURED: Mutex<Vec<String>> = Mutex::new(vec![]);
+
+    struct CapturingLogger;
+
+    impl Log for CapturingLogger {
+        fn enabled(&self, metadata: &Metadata) -> bool {
+            metadata.level() == Level::Trace
+        }
+
+        fn log(&self, record: &Record) {
+            if record.level() == Level::Trace && record.target() == "c8hooks::tracer" {
+                CAPTURED.lock().unwrap().push(record.args().to_string());
+            }
+        }
+
+        fn flush(&self) {}
+    }
+
+    #[test]
+    fn test_trace_lines() {
+        log::set_logger(&CapturingLogger).unwrap();
+        log::set_max_level(LevelFilter::Trace);
+
+        let rom = ROM::from_instructions(&[
+            Instruction::LoadRegByte(GeneralRegister::V0, 5),
+            Instruction::Add(GeneralRegister::V0, 1),
+            Instruction::ClearScreen,
+            Instruction::Jump(Address::new(0x200)),
+        ])
+        .unwrap();
+        let mut int = Interpreter::builder()
+            .extend_with(Tracer::new().with_max_lines(3))
+            .build(Chip8Interpreter::new_from_rom(rom));
+
+        let keys = Keys::from_raw([false; 16]);
+        for _ in 0..6 {
+            int.step(keys);
+        }
+        assert_eq!(
+            *CAPTURED.lock().unwrap(),
+            ["200: 6005 ld V0=05", "202: 7001 add V0=06", "204: 00E0 cls"]
+        );
+    }
+}
@@ -0,0 +1,43 @@
+//! Assembles a small program, runs it without a window and prints the screen
+//!
+//! `cargo run -p c8int --example demo`
+
+use c8asm::assemble_str;
+use c8common::control::{ControlledInterpreter, ControlledToInterpreter};
+use c8common::key::Keys;
+use c8int::Chip8Interpreter;
+
+const PROGRAM: &str = "
+; Draws the digits 0 to 7 across the top of the screen
+    ld v0, 0   ; digit
+    ld v1, 2   ; x
+    ld v2, 2   ; y
+next:
+    ld f, v0
+    drw v1, v2, 5
+    add v0, 1
+    add v1, 6
+    se v0, 8
+    jp next
+done:
+    jp done
+";
+
+const CYCLES: usize = 100;
+
+fn main() {
+    let rom = assemble_str(PROGRAM).expect("the demo program should assemble");
+    let mut interpreter = Chip8Interpreter::new_from_rom(rom).to_interpreter();
+
+    let no_keys = Keys::from_raw([false; 16]);
+    for _ in 0..CYCLES {
+        interpreter.step(no_keys);
+    }
+
+    print!("{}", interpreter.inner().display().to_ascii());
+    println!(
+        "Stopped at {:03X} in state {:?}",
+        interpreter.inner().program_counter(),
+        interpreter.state()
+    );
+}
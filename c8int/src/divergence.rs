@@ -0,0 +1,133 @@
+use crate::prelude::*;
+use crate::{Chip8Interpreter, Quirks, StateView};
+use asm::ROM;
+use c8common::control::ControlledToInterpreter;
+use c8common::key::Keys;
+
+/// Where two runs of the same program first stopped agreeing
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Divergence {
+    /// The number of steps both runs had taken, including the one that diverged
+    pub cycle: u32,
+    /// The address of the instruction executed in the diverging step, in the first run
+    pub at: Address,
+    pub difference: Difference,
+}
+
+/// The first part of the observable state found to differ
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Difference {
+    ProgramCounter {
+        left: Address,
+        right: Address,
+    },
+    Register {
+        register: GeneralRegister,
+        left: Datum,
+        right: Datum,
+    },
+    RegisterI {
+        left: u16,
+        right: u16,
+    },
+    Display,
+}
+
+/// Runs `rom` under both sets of quirks in lockstep, with no keys pressed, and reports the first
+/// step after which the program counter, registers or display differ.
+/// Programs using `Cxkk` will diverge on their own, as the random numbers aren't shared.
+pub fn first_divergence(
+    rom: &ROM,
+    left: Quirks,
+    right: Quirks,
+    max_cycles: u32,
+) -> Option<Divergence> {
+    let start = |quirks| {
+        Chip8Interpreter::new_from_rom(rom.clone())
+            .with_quirks(quirks)
+            .to_interpreter()
+    };
+    let (mut left, mut right) = (start(left), start(right));
+    let keys = Keys::from_raw([false; 16]);
+    for cycle in 1..=max_cycles {
+        let at = left.inner().view().program_counter;
+        left.step(keys);
+        right.step(keys);
+        if let Some(difference) = compare(left.inner().view(), right.inner().view()) {
+            return Some(Divergence {
+                cycle,
+                at,
+                difference,
+            });
+        }
+    }
+    None
+}
+
+fn compare(left: StateView, right: StateView) -> Option<Difference> {
+    if left.program_counter != right.program_counter {
+        return Some(Difference::ProgramCounter {
+            left: left.program_counter,
+            right: right.program_counter,
+        });
+    }
+    for (index, (l, r)) in left.registers.iter().zip(right.registers).enumerate() {
+        if *l != r {
+            return Some(Difference::Register {
+                register: GeneralRegister::from_byte(index as u8),
+                left: *l,
+                right: r,
+            });
+        }
+    }
+    if left.register_i != right.register_i {
+        return Some(Difference::RegisterI {
+            left: left.register_i,
+            right: right.register_i,
+        });
+    }
+    if left.display != right.display {
+        return Some(Difference::Display);
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shift_quirk_divergence() {
+        let rom = ROM::from_instructions(&[
+            Instruction::LoadRegByte(GeneralRegister::V0, 0x10),
+            Instruction::LoadRegByte(GeneralRegister::V1, 0x03),
+            Instruction::Shr {
+                x: GeneralRegister::V0,
+                y: GeneralRegister::V1,
+            },
+            Instruction::Jump(Address::new(0x206)),
+        ])
+        .unwrap();
+        let vy_quirk = Quirks {
+            shift_uses_vy: true,
+            ..Quirks::default()
+        };
+
+        assert_eq!(
+            first_divergence(&rom, Quirks::default(), vy_quirk, 10),
+            Some(Divergence {
+                cycle: 3,
+                at: Address::new(0x204),
+                difference: Difference::Register {
+                    register: GeneralRegister::V0,
+                    left: Datum(0x08),
+                    right: Datum(0x01),
+                },
+            })
+        );
+        assert_eq!(
+            first_divergence(&rom, Quirks::default(), Quirks::default(), 10),
+            None
+        );
+    }
+}
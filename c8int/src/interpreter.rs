@@ -1,11 +1,12 @@
 use crate::prelude::*;
+use crate::{Quirks, StateView};
 use asm::ROM;
-use c8asm::compilation::Assembler;
+use c8asm::compilation::{Assembler, DebugInfo};
 use c8asm::instruction_sets::Chip8InstructionSet;
 use c8common::control::{ControlledInterpreter, FrameInfo};
 use c8common::display::ScreenModification;
 use c8common::key::Keys;
-use c8common::memory::FONT_START_ADDR;
+use c8common::memory::{WriteProtected, FONT_START_ADDR};
 use log::{debug, error, info, warn};
 use rand::rngs::OsRng;
 use rand::Rng;
@@ -24,20 +25,52 @@ pub struct Chip8Interpreter {
     sound_timer: Datum,
 
     rng: OsRng,
+    strict: bool,
+    quirks: Quirks,
 }
 
 impl ControlledInterpreter for Chip8Interpreter {
     fn step(&mut self, keys: Keys, frame: &mut FrameInfo) {
-        // let orig_pc = self.program_counter;
+        self.step_with_decode(keys, frame, &mut Some)
+    }
+
+    fn step_with_decode(
+        &mut self,
+        keys: Keys,
+        frame: &mut FrameInfo,
+        on_decode: &mut dyn FnMut(Instruction) -> Option<Instruction>,
+    ) {
+        let orig_pc = self.program_counter;
         // assert_eq!(orig_pc.as_u16() % 2, 0);
         let d1 = self.fetch();
         let d2 = self.fetch();
-        let instruction = Self::decode((d1, d2)).expect("Instructions should be valid!");
+        let instruction = Self::decode((d1, d2))
+            .unwrap_or_else(|e| panic!("Instructions should be valid! {}", e));
+        let Some(instruction) = on_decode(instruction) else {
+            debug!("Skipping {:?} at 0x{:03X} for a hook", instruction, orig_pc);
+            return;
+        };
+
+        if self.strict && instruction == Instruction::Nop {
+            error!(
+                "Reached 0x0000 at 0x{:03X}, which is not a real instruction. Halting.",
+                orig_pc
+            );
+            self.program_counter = orig_pc;
+            frame.halt();
+            return;
+        }
 
         // println!("[Addr> {:04X}] (Op> {:02X}{:02X}) {:?}", orig_pc, d1, d2, instruction);
         // println!("Executing opcode=0x{:02X}{:02X} (pc=0x{:04X})", d1, d2, orig_pc.as_u16());
 
+        let registers = self.general_registers;
         self.execute(instruction, keys, frame);
+        for (index, (before, after)) in registers.iter().zip(self.general_registers).enumerate() {
+            if *before != after {
+                frame.mark_register_changed(GeneralRegister::from_byte(index as u8));
+            }
+        }
     }
 
     fn display(&self) -> &Display {
@@ -116,20 +149,24 @@ impl Chip8Interpreter {
         datum
     }
 
-    fn decode(data: (Datum, Datum)) -> Result<Instruction, RawInstruction> {
+    fn decode(data: (Datum, Datum)) -> Result<Instruction, InstructionDecodeError> {
         debug!("Decoding 0x{:02X}{:02X}", data.0, data.1);
         let processing = Instruction::try_from_data(data.into());
 
         processing
             .tap_ok(|inst| debug!("Instruction is {:?}", inst))
-            .map_err(|e| e.invalid_data().unwrap())
+            .tap_err(|e| error!("{}", e))
     }
 
     fn execute(&mut self, instruction: Instruction, keys: Keys, frame: &mut FrameInfo) {
+        frame.set_executed(instruction);
         match instruction {
             Instruction::Nop => {
                 info!("Nop")
             }
+            Instruction::Sys(addr) => {
+                info!("Ignoring machine code call to {:X}", addr)
+            }
             Instruction::ClearScreen => {
                 info!("Clear screen");
                 self.display.clear();
@@ -162,8 +199,7 @@ impl Chip8Interpreter {
                 let contents = self.get_register(reg);
                 if contents.0 == byte {
                     info!("Skipping next instruction! (EQ)");
-                    self.increment_program_counter();
-                    self.increment_program_counter();
+                    self.skip_next_instruction();
                 } else {
                     info!("Not skipping next instruction! (NE)");
                 }
@@ -172,8 +208,7 @@ impl Chip8Interpreter {
                 let contents = self.get_register(reg);
                 if contents.0 != byte {
                     info!("Skipping next instruction! (NE)");
-                    self.increment_program_counter();
-                    self.increment_program_counter();
+                    self.skip_next_instruction();
                 } else {
                     info!("Not skipping next instruction! (EQ)");
                 }
@@ -183,8 +218,7 @@ impl Chip8Interpreter {
                 let c2 = self.get_register(r2);
                 if c1 == c2 {
                     info!("Skipping next instruction! (EQ)");
-                    self.increment_program_counter();
-                    self.increment_program_counter();
+                    self.skip_next_instruction();
                 } else {
                     info!("Not skipping next instruction! (NE)");
                 }
@@ -195,7 +229,7 @@ impl Chip8Interpreter {
             }
             Instruction::Add(reg, byte) => {
                 info!("Add {} to {:?}", byte, reg);
-                self.set_register(reg, Datum(self.register(reg).0.overflowing_add(byte).0));
+                self.set_register(reg, self.get_register(reg).wrapping_add(Datum(byte)));
             }
             Instruction::CopyRegToReg { x: rx, y: ry } => {
                 info!("Copy from {:?} to {:?}", ry, rx);
@@ -204,59 +238,59 @@ impl Chip8Interpreter {
             Instruction::Or { x: rx, y: ry } => {
                 info!("OR {:?}, {:?}", rx, ry);
                 self.set_register(rx, self.get_register(rx) | self.get_register(ry));
+                self.logic_quirk();
             }
             Instruction::And { x: rx, y: ry } => {
                 info!("AND {:?}, {:?}", rx, ry);
                 self.set_register(rx, self.get_register(rx) & self.get_register(ry));
+                self.logic_quirk();
             }
             Instruction::Xor { x: rx, y: ry } => {
                 info!("XOR {:?}, {:?}", rx, ry);
                 self.set_register(rx, self.get_register(rx) ^ self.get_register(ry));
+                self.logic_quirk();
             }
             Instruction::AddReg { x: rx, y: ry } => {
                 info!("ADD {:?}, {:?}", rx, ry);
-                let (num, overflow) = self
-                    .get_register(rx)
-                    .0
-                    .overflowing_add(self.get_register(ry).0);
+                let (num, overflow) = self.get_register(rx).overflowing_add(self.get_register(ry));
+                // The flag is written last so it wins when the destination is VF
+                self.set_register(rx, num);
                 self.set_vf(if overflow { Datum(1) } else { Datum(0) });
-                self.set_register(rx, Datum(num));
             }
             Instruction::Sub { x: rx, y: ry } => {
                 info!("SUB {:?}, {:?}", rx, ry);
                 // If Vx > Vy, then VF is set to 1, otherwise 0. Then Vy is subtracted from Vx, and the results stored in Vx.
                 let (x, y) = (self.get_register(rx), self.get_register(ry));
+                self.set_register(rx, x.wrapping_sub(y));
                 self.set_vf(Datum(u8::from(x > y)));
-                self.set_register(rx, Datum(x.0.overflowing_sub(y.0).0));
             }
-            Instruction::Shr(rx) => {
+            Instruction::Shr { x: rx, y: ry } => {
                 info!("SHR {:?}", rx);
-                let number = self.get_register(rx).0;
+                let number = self.shift_source(rx, ry).0;
                 let right = number & 0b1;
-                self.set_vf(Datum(u8::from(right != 0)));
                 self.set_register(rx, Datum(number >> 1));
+                self.set_vf(Datum(u8::from(right != 0)));
             }
             Instruction::SubN { x: rx, y: ry } => {
                 info!("SUBN {:?}, {:?}", rx, ry);
                 // If Vy > Vx, then VF is set to 1, otherwise 0. Then Vx is subtracted from Vy, and the results stored in Vx.
                 let (x, y) = (self.get_register(rx), self.get_register(ry));
+                self.set_register(rx, y.wrapping_sub(x));
                 self.set_vf(Datum(u8::from(y > x)));
-                self.set_register(rx, Datum(y.0.overflowing_sub(x.0).0));
             }
-            Instruction::Shl(rx) => {
+            Instruction::Shl { x: rx, y: ry } => {
                 info!("SHL {:?}", rx);
-                let number = self.get_register(rx).0;
+                let number = self.shift_source(rx, ry).0;
                 let right = number & 0b10000000;
-                self.set_vf(Datum(u8::from(right != 0)));
                 self.set_register(rx, Datum(number << 1));
+                self.set_vf(Datum(u8::from(right != 0)));
             }
             Instruction::SkipRegistersNotEqual(r1, r2) => {
                 let c1 = self.get_register(r1);
                 let c2 = self.get_register(r2);
                 if c1 != c2 {
                     info!("Skipping next instruction! (NE)");
-                    self.increment_program_counter();
-                    self.increment_program_counter();
+                    self.skip_next_instruction();
                 } else {
                     info!("Not skipping next instruction! (EQ)");
                 }
@@ -266,9 +300,14 @@ impl Chip8Interpreter {
                 self.register_i = value.as_u16();
             }
             Instruction::JumpRelative(rel_addr) => {
-                info!("Relative jump to V0 + {:02X}", rel_addr);
-                let v0 = self.get_register(GeneralRegister::V0);
-                let target = v0.0 as u16 + rel_addr.as_u16();
+                let offset_reg = if self.quirks.jump_uses_vx {
+                    GeneralRegister::from_nibble(rel_addr.to_nibbles()[0])
+                } else {
+                    GeneralRegister::V0
+                };
+                info!("Relative jump to {:?} + {:02X}", offset_reg, rel_addr);
+                let offset = self.get_register(offset_reg);
+                let target = offset.0 as u16 + rel_addr.as_u16();
                 if target & 0xF000 != 0 {
                     error!("Invalid jump address! 0x{:X} is out of bounds!", target);
                     panic!()
@@ -295,16 +334,22 @@ impl Chip8Interpreter {
                     "Display sprite; RX={:?} RY={:?} bytes={}",
                     vx, vy, number_of_bytes
                 );
-                let addr = Address::new(self.register_i);
+                let addr = self.i_offset(0);
                 let x_coord = self.get_register(vx);
                 let y_coord = self.get_register(vy);
                 debug!("sprite={:03X} x={} y={}", addr, x_coord.0, y_coord.0);
-                let m = self.display.sprite(
+                let data =
+                    self.sprite_data(self.display.sprite_len(number_of_bytes as usize) as u16);
+                let m = self.display.sprite_with_clipping(
                     x_coord,
                     y_coord,
-                    self.memory.substring(addr, number_of_bytes),
+                    &data,
+                    self.quirks.clipping,
                 );
                 self.set_vf(Datum(u8::from(m == ScreenModification::Clears)));
+                if self.quirks.display_wait {
+                    frame.wait_for_vblank();
+                }
                 frame.modify_screen()
             }
             Instruction::SkipPressed(reg) => {
@@ -312,8 +357,7 @@ impl Chip8Interpreter {
                 let key = Keys::from_datum(data);
                 if (keys & key).pressed() {
                     info!("Skipping next instruction! ({:?} pressed)", key);
-                    self.increment_program_counter();
-                    self.increment_program_counter();
+                    self.skip_next_instruction();
                 } else {
                     info!("Not skipping next instruction! ({:?} not pressed)", key);
                 }
@@ -323,8 +367,7 @@ impl Chip8Interpreter {
                 let key = Keys::from_datum(data);
                 if !(keys & key).pressed() {
                     info!("Skipping next instruction! ({:?} not pressed)", key);
-                    self.increment_program_counter();
-                    self.increment_program_counter();
+                    self.skip_next_instruction();
                 } else {
                     info!("Not skipping next instruction! ({:?} pressed)", key);
                 }
@@ -350,11 +393,14 @@ impl Chip8Interpreter {
             }
             Instruction::AddI(reg) => {
                 info!("Add {:?} to I", reg);
-                self.set_i(
-                    self.get_i()
-                        .overflowing_add(self.get_register(reg).0 as u16)
-                        .0,
-                );
+                let sum = self
+                    .get_i()
+                    .overflowing_add(self.get_register(reg).0 as u16)
+                    .0;
+                if self.quirks.add_i_sets_vf {
+                    self.set_vf(Datum(u8::from(sum > 0x0FFF)));
+                }
+                self.set_i(sum & Address::MAX.as_u16());
             }
             Instruction::GetSprite(reg) => {
                 info!("Get sprite location for {:?}", reg);
@@ -369,24 +415,40 @@ impl Chip8Interpreter {
                 let units = num % 10;
                 let tens = (num / 10) % 10;
                 let hundreds = (num / 100) % 10;
-                let i = self.register_i;
-                self.memory[Address::new(i)] = Datum(hundreds);
-                self.memory[Address::new(i + 1)] = Datum(tens);
-                self.memory[Address::new(i + 2)] = Datum(units);
+                let written =
+                    [hundreds, tens, units]
+                        .into_iter()
+                        .zip(0..)
+                        .try_for_each(|(digit, offset)| {
+                            self.memory.write(self.i_offset(offset), Datum(digit))
+                        });
+                if let Err(e) = written {
+                    self.write_protected(e, frame);
+                }
             }
             Instruction::WriteMultiple(until_reg) => {
                 info!("Read to I+ until {:?}", until_reg);
                 for (i, reg) in until_reg.until_including().enumerate() {
                     let data = self.get_register(reg);
-                    self.memory[Address::new(self.register_i + i as u16)] = data;
+                    let addr = self.i_offset(i as u16);
+                    if let Err(e) = self.memory.write(addr, data) {
+                        self.write_protected(e, frame);
+                        return;
+                    }
                 }
+                self.increment_i_after_load_store(until_reg);
             }
             Instruction::ReadMultiple(until_reg) => {
                 info!("Read from I+ through {:?}", until_reg);
                 for (i, reg) in until_reg.until_including().enumerate() {
-                    let data = self.memory[Address::new(self.register_i + i as u16)];
+                    let data = self.memory.read(self.i_offset(i as u16));
                     self.set_register(reg, data);
                 }
+                self.increment_i_after_load_store(until_reg);
+            }
+            Instruction::SelectPlanes(mask) => {
+                info!("Select planes {:02b}", mask);
+                self.display.select_planes(mask);
             }
         }
     }
@@ -405,6 +467,51 @@ impl Chip8Interpreter {
         *self.vf_mut() = to;
     }
 
+    fn logic_quirk(&mut self) {
+        if self.quirks.logic_resets_vf {
+            self.set_vf(Datum(0));
+        }
+    }
+
+    /// Skips the following instruction, which is 4 bytes long if it is XO-CHIP's `F000 nnnn`
+    fn skip_next_instruction(&mut self) {
+        let next = self.program_counter.as_u16();
+        let is_long = next < Address::MAX.as_u16()
+            && self.memory[Address::new(next)] == Datum(0xF0)
+            && self.memory[Address::new(next + 1)] == Datum(0x00);
+        let width = if is_long { 4 } else { 2 };
+        for _ in 0..width {
+            self.increment_program_counter();
+        }
+    }
+
+    fn shift_source(&self, x: GeneralRegister, y: GeneralRegister) -> Datum {
+        self.get_register(if self.quirks.shift_uses_vy { y } else { x })
+    }
+
+    fn increment_i_after_load_store(&mut self, until_reg: GeneralRegister) {
+        if self.quirks.load_store_increments_i {
+            self.register_i = self.i_offset(until_reg.index() as u16 + 1).as_u16();
+        }
+    }
+
+    /// The address `offset` bytes past I, wrapping at the end of memory so I stays within 12 bits
+    fn i_offset(&self, offset: u16) -> Address {
+        Address::new(self.register_i.wrapping_add(offset) & Address::MAX.as_u16())
+    }
+
+    /// `len` bytes of sprite data from I, wrapping at the end of memory
+    fn sprite_data(&self, len: u16) -> Vec<Datum> {
+        (0..len)
+            .map(|offset| self.memory[self.i_offset(offset)])
+            .collect()
+    }
+
+    fn write_protected(&mut self, e: WriteProtected, frame: &mut FrameInfo) {
+        error!("Refused a write, {}. Halting.", e);
+        frame.halt();
+    }
+
     fn empty() -> Self {
         Self {
             program_counter: Address::PROGRAM_START,
@@ -416,26 +523,72 @@ impl Chip8Interpreter {
             delay_timer: Datum(0),
             sound_timer: Datum(0),
             rng: OsRng,
+            strict: false,
+            quirks: Quirks::default(),
+        }
+    }
+
+    /// In strict mode, `0x0000` (`Nop`) halts the interpreter instead of doing nothing
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    pub fn with_quirks(mut self, quirks: Quirks) -> Self {
+        self.quirks = quirks;
+        self
+    }
+
+    pub fn quirks(&self) -> Quirks {
+        self.quirks
+    }
+
+    /// Starts with `V0`..`VF` already set, so a single instruction can be tested without a setup
+    /// program
+    pub fn with_registers(mut self, registers: [Datum; 16]) -> Self {
+        self.general_registers = registers;
+        self
+    }
+
+    /// Replaces all of memory, including the font and the program
+    pub fn with_memory(mut self, memory: Memory) -> Self {
+        self.memory = memory;
+        self
+    }
+
+    pub fn view(&self) -> StateView<'_> {
+        StateView {
+            program_counter: self.program_counter,
+            registers: self.general_registers,
+            register_i: self.register_i,
+            delay_timer: self.delay_timer,
+            sound_timer: self.sound_timer,
+            stack: &self.stack,
+            memory: &self.memory,
+            display: &self.display,
         }
     }
 
     pub fn new_assembled<F: FnOnce(&mut Assembler) -> &mut Assembler>(with: F) -> Self {
-        Self::new_from_rom(Self::assembled_program(with))
+        let (program, debug) = Self::assembled_program(with);
+        Self::new_from_assembled(program, &debug)
     }
 
     pub fn new_assembled_save<F: FnOnce(&mut Assembler) -> &mut Assembler>(
         to: impl AsRef<std::path::Path>,
         with: F,
     ) -> Result<Self, std::io::Error> {
-        let program = Self::assembled_program(with);
+        let (program, debug) = Self::assembled_program(with);
         program.save(to)?;
-        Ok(Self::new_from_rom(program))
+        Ok(Self::new_from_assembled(program, &debug))
     }
 
-    fn assembled_program<F: FnOnce(&mut Assembler) -> &mut Assembler>(with: F) -> ROM {
+    fn assembled_program<F: FnOnce(&mut Assembler) -> &mut Assembler>(with: F) -> (ROM, DebugInfo) {
         let mut assembler = Assembler::new();
         (with)(&mut assembler);
-        assembler.assemble::<Chip8InstructionSet>().unwrap() // TODO
+        assembler
+            .assemble_with_debug::<Chip8InstructionSet>()
+            .unwrap() // TODO
     }
 
     pub fn new_from_mem_file(path: impl AsRef<std::path::Path>) -> Self {
@@ -461,4 +614,968 @@ impl Chip8Interpreter {
             ..Self::empty()
         }
     }
+
+    /// Useful for testing a subroutine on its own
+    pub fn new_from_rom_starting_at(rom: ROM, start: Address) -> Self {
+        Self {
+            program_counter: start,
+            ..Self::new_from_rom(rom)
+        }
+    }
+
+    /// Starts at the program's `.entry` label if it has one, or the start of the program if not
+    pub fn new_from_assembled(rom: ROM, debug: &DebugInfo) -> Self {
+        Self::new_from_rom_starting_at(rom, debug.entry().unwrap_or(Address::PROGRAM_START))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use c8common::control::cycles::CycleModel;
+    use c8common::control::execute::{Interpreter, InterpreterEvent, StopReason, TimerSource};
+    use c8common::control::{ControlledToInterpreter, InterpreterState};
+    use c8common::hooks::disabled::EnabledHook;
+    use c8common::hooks::{HookInternalAccess, InterpreterHook};
+    use c8common::NUMBER_OF_ADDRESSES;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    fn no_keys() -> Keys {
+        Keys::from_raw([false; 16])
+    }
+
+    fn zeroed() -> Memory {
+        Memory::from_bytes(vec![0; NUMBER_OF_ADDRESSES]).unwrap()
+    }
+
+    #[test]
+    fn test_strict_halts_on_empty_memory() {
+        let mut int = Chip8Interpreter::new_from_memory(zeroed())
+            .with_strict(true)
+            .to_interpreter();
+        for _ in 0..4 {
+            assert!(int.step(no_keys()).is_none());
+        }
+        assert_eq!(*int.state(), InterpreterState::Halted);
+        assert_eq!(int.inner().program_counter(), Address::PROGRAM_START);
+    }
+
+    #[test]
+    fn test_lenient_runs_through_empty_memory() {
+        let mut int = Chip8Interpreter::new_from_memory(zeroed()).to_interpreter();
+        for _ in 0..4 {
+            int.step(no_keys());
+        }
+        assert_eq!(*int.state(), InterpreterState::Normal);
+        assert_eq!(int.inner().program_counter(), Address::new(0x208));
+    }
+
+    #[test]
+    fn test_timer_rate_independent_of_frequency() {
+        for frequency in [60, 500, 1000, 4096] {
+            let rom = ROM::from_instructions(&[
+                Instruction::LoadRegByte(GeneralRegister::V0, 0xFF),
+                Instruction::SetDelayTimer(GeneralRegister::V0),
+                Instruction::Add(GeneralRegister::V1, 1),
+                Instruction::Jump(Address::new(0x204)),
+            ])
+            .unwrap();
+            let mut int = Chip8Interpreter::new_from_rom(rom)
+                .to_interpreter()
+                .with_frequency(frequency);
+            int.step(no_keys());
+            int.step(no_keys());
+            let start = int.inner().delay_timer_register().0;
+
+            for _ in 0..frequency {
+                int.step(no_keys());
+            }
+            let decrements = start - int.inner().delay_timer_register().0;
+            assert_eq!(decrements, 60, "at {} steps per second", frequency);
+        }
+    }
+
+    #[test]
+    fn test_zero_frequency_still_steps() {
+        let rom = ROM::from_instructions(&[Instruction::Add(GeneralRegister::V1, 1)]).unwrap();
+        let mut int = Chip8Interpreter::new_from_rom(rom)
+            .to_interpreter()
+            .with_frequency(0);
+        int.step(no_keys());
+        assert_eq!(int.inner().register(GeneralRegister::V1).0, 1);
+    }
+
+    #[test]
+    fn test_external_timer_source() {
+        let rom = ROM::from_instructions(&[
+            Instruction::LoadRegByte(GeneralRegister::V0, 0xFF),
+            Instruction::SetDelayTimer(GeneralRegister::V0),
+            Instruction::Add(GeneralRegister::V1, 1),
+            Instruction::Jump(Address::new(0x204)),
+        ])
+        .unwrap();
+        let mut int = Chip8Interpreter::new_from_rom(rom)
+            .to_interpreter()
+            .with_frequency(600)
+            .with_timer_source(TimerSource::External);
+        for _ in 0..1000 {
+            int.step(no_keys());
+        }
+        assert_eq!(int.inner().delay_timer_register().0, 0xFF);
+
+        for _ in 0..3 {
+            int.advance_timers(Duration::from_millis(250));
+        }
+        int.advance_timers(Duration::from_millis(249));
+        assert_eq!(int.inner().delay_timer_register().0, 0xFF - 59);
+        int.advance_timers(Duration::from_millis(1));
+        assert_eq!(int.inner().delay_timer_register().0, 0xFF - 60);
+    }
+
+    #[test]
+    fn test_primed_timers_tick_down() {
+        let rom = ROM::from_instructions(&[
+            Instruction::Add(GeneralRegister::V1, 1),
+            Instruction::Jump(Address::new(0x200)),
+        ])
+        .unwrap();
+        let mut int = Chip8Interpreter::new_from_rom(rom)
+            .to_interpreter()
+            .with_frequency(60);
+        int.inner_mut().set_delay_timer(Datum(30));
+        int.inner_mut().set_sound_timer(Datum(5));
+
+        for _ in 0..10 {
+            int.step(no_keys());
+        }
+        assert_eq!(*int.inner().delay_timer_register(), Datum(20));
+        assert_eq!(*int.inner().sound_timer_register(), Datum(0));
+    }
+
+    #[test]
+    fn test_fast_forward_timers() {
+        let rom = ROM::from_instructions(&[
+            Instruction::LoadRegByte(GeneralRegister::V0, 120),
+            Instruction::SetDelayTimer(GeneralRegister::V0),
+            Instruction::GetDelayTimer(GeneralRegister::V1),
+            Instruction::SkipIfEqual(GeneralRegister::V1, 0),
+            Instruction::Jump(Address::new(0x204)),
+            Instruction::LoadRegByte(GeneralRegister::V2, 1),
+        ])
+        .unwrap();
+        let mut int = Chip8Interpreter::new_from_rom(rom)
+            .to_interpreter()
+            .with_frequency(500);
+        let done = |int: &Chip8Interpreter| int.get_register(GeneralRegister::V2) == Datum(1);
+        int.step(no_keys());
+        int.step(no_keys());
+
+        int.fast_forward_timers(Duration::from_millis(1000));
+        assert_eq!(*int.inner().delay_timer_register(), Datum(60));
+        int.fast_forward_timers(Duration::from_secs(5));
+        assert_eq!(*int.inner().delay_timer_register(), Datum(0));
+        assert_eq!(int.run_until(4, done).reason, StopReason::Reached);
+    }
+
+    #[derive(Debug)]
+    struct SlowSprites;
+
+    impl CycleModel for SlowSprites {
+        fn cost(&self, instruction: Instruction) -> u32 {
+            match instruction {
+                Instruction::DisplaySprite { .. } => 3,
+                _ => 1,
+            }
+        }
+    }
+
+    #[test]
+    fn test_cycle_model_stalls_after_sprite() {
+        let rom = ROM::from_instructions(&[
+            Instruction::DisplaySprite {
+                x: GeneralRegister::V0,
+                y: GeneralRegister::V0,
+                number_of_bytes: 1,
+            },
+            Instruction::Add(GeneralRegister::V1, 1),
+            Instruction::Jump(Address::new(0x202)),
+        ])
+        .unwrap();
+        let mut int = Chip8Interpreter::new_from_rom(rom)
+            .to_interpreter()
+            .with_cycle_model(SlowSprites);
+
+        int.step(no_keys());
+        assert_eq!(int.inner().program_counter(), Address::new(0x202));
+        int.step(no_keys());
+        int.step(no_keys());
+        assert_eq!(int.inner().program_counter(), Address::new(0x202));
+        int.step(no_keys());
+        assert_eq!(int.inner().program_counter(), Address::new(0x204));
+        assert_eq!(int.inner().get_register(GeneralRegister::V1), Datum(1));
+    }
+
+    #[test]
+    fn test_write_callback_fires_on_store() {
+        let rom = ROM::from_instructions(&[
+            Instruction::LoadRegByte(GeneralRegister::V0, 7),
+            Instruction::LoadRegByte(GeneralRegister::V1, 9),
+            Instruction::LoadImmediate(Address::new(0x300)),
+            Instruction::WriteMultiple(GeneralRegister::V1),
+            Instruction::Add(GeneralRegister::V2, 1),
+            Instruction::Jump(Address::new(0x208)),
+        ])
+        .unwrap();
+        let written = Arc::new(Mutex::new(vec![]));
+        let mut memory = rom.to_memory();
+        memory.on_write(Address::new(0x301), {
+            let written = written.clone();
+            move |addr, datum| written.lock().unwrap().push((addr, datum))
+        });
+        let mut int = Chip8Interpreter::new_from_memory(memory).to_interpreter();
+
+        for _ in 0..3 {
+            int.step(no_keys());
+        }
+        assert!(written.lock().unwrap().is_empty());
+        int.step(no_keys());
+        assert_eq!(*written.lock().unwrap(), [(Address::new(0x301), Datum(9))]);
+        assert_eq!(int.inner().memory()[Address::new(0x300)], Datum(7));
+    }
+
+    #[test]
+    fn test_write_protect_halts() {
+        let rom = ROM::from_instructions(&[
+            Instruction::LoadRegByte(GeneralRegister::V0, 123),
+            Instruction::LoadImmediate(Address::new(0x300)),
+            Instruction::BCD(GeneralRegister::V0),
+            Instruction::LoadImmediate(Address::new(0x200)),
+            Instruction::WriteMultiple(GeneralRegister::V0),
+            Instruction::Jump(Address::new(0x20A)),
+        ])
+        .unwrap();
+        let mut memory = rom.to_memory();
+        memory.protect(Address::PROGRAM_START..Address::new(0x20C));
+        let mut int = Chip8Interpreter::new_from_memory(memory).to_interpreter();
+
+        for _ in 0..5 {
+            int.step(no_keys());
+        }
+        assert_eq!(
+            int.inner().memory().substring(Address::new(0x300), 3),
+            [Datum(1), Datum(2), Datum(3)]
+        );
+        assert_eq!(int.inner().memory()[Address::new(0x200)], Datum(0x60));
+        assert_eq!(*int.state(), InterpreterState::Halted);
+    }
+
+    #[test]
+    fn test_vip_quirks() {
+        let rom = ROM::from_instructions(&[
+            Instruction::LoadRegByte(GeneralRegister::V1, 0b110),
+            Instruction::Shr {
+                x: GeneralRegister::V0,
+                y: GeneralRegister::V1,
+            },
+            Instruction::LoadImmediate(Address::new(0x300)),
+            Instruction::WriteMultiple(GeneralRegister::V1),
+        ])
+        .unwrap();
+        let run = |quirks| {
+            let mut int = Chip8Interpreter::new_from_rom(rom.clone())
+                .with_quirks(quirks)
+                .to_interpreter();
+            for _ in 0..4 {
+                int.step(no_keys());
+            }
+            (
+                int.inner().get_register(GeneralRegister::V0),
+                int.inner().register_i,
+            )
+        };
+
+        assert_eq!(run(Quirks::default()), (Datum(0), 0x300));
+        assert_eq!(run(Quirks::cosmac_vip()), (Datum(0b11), 0x302));
+    }
+
+    #[test]
+    fn test_i_wraps_at_end_of_memory() {
+        let rom = ROM::from_instructions(&[
+            Instruction::LoadImmediate(Address::new(0xFFE)),
+            Instruction::DisplaySprite {
+                x: GeneralRegister::V0,
+                y: GeneralRegister::V0,
+                number_of_bytes: 4,
+            },
+            Instruction::ReadMultiple(GeneralRegister::V3),
+        ])
+        .unwrap();
+        let mut int = Chip8Interpreter::new_from_rom(rom)
+            .with_quirks(Quirks {
+                load_store_increments_i: true,
+                ..Quirks::default()
+            })
+            .to_interpreter();
+        for _ in 0..3 {
+            int.step(no_keys());
+        }
+        assert_eq!(int.inner().register_i, 0x002);
+    }
+
+    #[test]
+    fn test_add_i_sets_vf_quirk() {
+        let rom = ROM::from_instructions(&[
+            Instruction::LoadImmediate(Address::new(0xFF0)),
+            Instruction::LoadRegByte(GeneralRegister::V0, 0x20),
+            Instruction::AddI(GeneralRegister::V0),
+        ])
+        .unwrap();
+        let run = |add_i_sets_vf| {
+            let mut int = Chip8Interpreter::new_from_rom(rom.clone())
+                .with_quirks(Quirks {
+                    add_i_sets_vf,
+                    ..Quirks::default()
+                })
+                .to_interpreter();
+            for _ in 0..3 {
+                int.step(no_keys());
+            }
+            (
+                int.inner().get_register(GeneralRegister::VF),
+                int.inner().register_i,
+            )
+        };
+
+        assert_eq!(run(false), (Datum(0), 0x010));
+        assert_eq!(run(true), (Datum(1), 0x010));
+    }
+
+    fn vf_after(instructions: &[Instruction]) -> Datum {
+        let rom = ROM::from_instructions(instructions).unwrap();
+        let mut int = Chip8Interpreter::new_from_rom(rom).to_interpreter();
+        for _ in instructions {
+            int.step(no_keys());
+        }
+        int.inner().get_register(GeneralRegister::VF)
+    }
+
+    #[test]
+    fn test_add_reg_into_vf_keeps_carry() {
+        let add = |vf, v1| {
+            vf_after(&[
+                Instruction::LoadRegByte(GeneralRegister::VF, vf),
+                Instruction::LoadRegByte(GeneralRegister::V1, v1),
+                Instruction::AddReg {
+                    x: GeneralRegister::VF,
+                    y: GeneralRegister::V1,
+                },
+            ])
+        };
+        assert_eq!(add(0xF0, 0x20), Datum(1));
+        assert_eq!(add(0x10, 0x20), Datum(0));
+    }
+
+    #[test]
+    fn test_sub_into_vf_keeps_flag() {
+        let sub = |vf, v1| {
+            vf_after(&[
+                Instruction::LoadRegByte(GeneralRegister::VF, vf),
+                Instruction::LoadRegByte(GeneralRegister::V1, v1),
+                Instruction::Sub {
+                    x: GeneralRegister::VF,
+                    y: GeneralRegister::V1,
+                },
+            ])
+        };
+        assert_eq!(sub(0x30, 0x10), Datum(1));
+        assert_eq!(sub(0x10, 0x30), Datum(0));
+    }
+
+    #[test]
+    fn test_subn_into_vf_keeps_flag() {
+        let subn = |vf, v1| {
+            vf_after(&[
+                Instruction::LoadRegByte(GeneralRegister::VF, vf),
+                Instruction::LoadRegByte(GeneralRegister::V1, v1),
+                Instruction::SubN {
+                    x: GeneralRegister::VF,
+                    y: GeneralRegister::V1,
+                },
+            ])
+        };
+        assert_eq!(subn(0x10, 0x30), Datum(1));
+        assert_eq!(subn(0x30, 0x10), Datum(0));
+    }
+
+    #[test]
+    fn test_shifts_into_vf_keep_flag() {
+        let shift = |vf, instruction| {
+            vf_after(&[
+                Instruction::LoadRegByte(GeneralRegister::VF, vf),
+                instruction,
+            ])
+        };
+        let shr = Instruction::Shr {
+            x: GeneralRegister::VF,
+            y: GeneralRegister::VF,
+        };
+        let shl = Instruction::Shl {
+            x: GeneralRegister::VF,
+            y: GeneralRegister::VF,
+        };
+        assert_eq!(shift(0b0000_0011, shr), Datum(1));
+        assert_eq!(shift(0b0000_0010, shr), Datum(0));
+        assert_eq!(shift(0b1100_0000, shl), Datum(1));
+        assert_eq!(shift(0b0100_0000, shl), Datum(0));
+    }
+
+    #[test]
+    fn test_logic_resets_vf_quirk() {
+        let rom = ROM::from_instructions(&[
+            Instruction::LoadRegByte(GeneralRegister::VF, 0x42),
+            Instruction::LoadRegByte(GeneralRegister::V1, 0b01),
+            Instruction::Or {
+                x: GeneralRegister::V0,
+                y: GeneralRegister::V1,
+            },
+        ])
+        .unwrap();
+        let run = |logic_resets_vf| {
+            let mut int = Chip8Interpreter::new_from_rom(rom.clone())
+                .with_quirks(Quirks {
+                    logic_resets_vf,
+                    ..Quirks::default()
+                })
+                .to_interpreter();
+            for _ in 0..3 {
+                int.step(no_keys());
+            }
+            (
+                int.inner().get_register(GeneralRegister::V0),
+                int.inner().get_register(GeneralRegister::VF),
+            )
+        };
+
+        assert_eq!(run(false), (Datum(0b01), Datum(0x42)));
+        assert_eq!(run(true), (Datum(0b01), Datum(0)));
+    }
+
+    #[test]
+    fn test_step_answers_wait_for_key() {
+        let rom = ROM::from_instructions(&[
+            Instruction::WaitForKey(GeneralRegister::V3),
+            Instruction::Add(GeneralRegister::V3, 1),
+        ])
+        .unwrap();
+        let mut int = Chip8Interpreter::new_from_rom(rom).to_interpreter();
+        int.step(no_keys());
+        assert_eq!(
+            *int.state(),
+            InterpreterState::WaitForKey(GeneralRegister::V3)
+        );
+        int.step(no_keys());
+        assert_eq!(
+            *int.state(),
+            InterpreterState::WaitForKey(GeneralRegister::V3)
+        );
+
+        int.step(Keys::from_number(0xA));
+        assert_eq!(*int.state(), InterpreterState::Normal);
+        assert_eq!(int.inner().get_register(GeneralRegister::V3), Datum(0xB));
+    }
+
+    #[test]
+    fn test_stack_depth_and_top() {
+        let rom = ROM::from_instructions(&[
+            Instruction::Call(Address::new(0x202)),
+            Instruction::Call(Address::new(0x204)),
+            Instruction::ClearScreen,
+        ])
+        .unwrap();
+        let mut int = Chip8Interpreter::new_from_rom(rom).to_interpreter();
+        assert_eq!(int.inner().stack_depth(), 0);
+        assert_eq!(int.inner().stack_top(), None);
+
+        int.step(no_keys());
+        int.step(no_keys());
+        assert_eq!(int.inner().stack_depth(), 2);
+        assert_eq!(int.inner().stack_top(), Some(Address::new(0x204)));
+    }
+
+    #[test]
+    fn test_view_after_steps() {
+        let rom = ROM::from_instructions(&[
+            Instruction::LoadRegByte(GeneralRegister::V3, 0x42),
+            Instruction::LoadImmediate(Address::new(0x321)),
+            Instruction::SetDelayTimer(GeneralRegister::V3),
+            Instruction::Call(Address::new(0x208)),
+            Instruction::ClearScreen,
+        ])
+        .unwrap();
+        let mut int = Chip8Interpreter::new_from_rom(rom).to_interpreter();
+        for _ in 0..4 {
+            int.step(no_keys());
+        }
+
+        let view = int.inner().view();
+        assert_eq!(view.program_counter, Address::new(0x208));
+        assert_eq!(view.registers[3], Datum(0x42));
+        assert_eq!(view.register_i, 0x321);
+        assert_eq!(view.delay_timer, *int.inner().delay_timer_register());
+        assert_eq!(view.sound_timer, Datum(0));
+        assert_eq!(view.stack, &[Address::new(0x208)]);
+        assert_eq!(view.memory[Address::new(0x200)], Datum(0x63));
+        assert!(std::ptr::eq(view.display, int.inner().display()));
+    }
+
+    #[test]
+    fn test_event_sender() {
+        let rom = ROM::from_instructions(&[
+            Instruction::GetSprite(GeneralRegister::V0),
+            Instruction::DisplaySprite {
+                x: GeneralRegister::V0,
+                y: GeneralRegister::V0,
+                number_of_bytes: 5,
+            },
+            Instruction::Jump(Address::new(0x204)),
+        ])
+        .unwrap();
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let mut int = Interpreter::builder()
+            .build(Chip8Interpreter::new_from_rom(rom))
+            .with_event_sender(sender);
+        for _ in 0..4 {
+            int.step(no_keys());
+        }
+
+        let events = receiver.try_iter().collect::<Vec<_>>();
+        assert!(matches!(
+            events[..],
+            [
+                InterpreterEvent::ScreenUpdated(_),
+                InterpreterEvent::BusyWaiting
+            ]
+        ));
+        if let InterpreterEvent::ScreenUpdated(display) = &events[0] {
+            assert_eq!(
+                display.raw()[0][0..4],
+                [true, true, true, true].map(Into::into)
+            );
+        }
+    }
+
+    #[test]
+    fn test_buzzer_stops_with_sound_timer() {
+        let rom = ROM::from_instructions(&[
+            Instruction::LoadRegByte(GeneralRegister::V0, 2),
+            Instruction::SetSoundTimer(GeneralRegister::V0),
+            Instruction::Add(GeneralRegister::V1, 1),
+            Instruction::Jump(Address::new(0x204)),
+        ])
+        .unwrap();
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let mut int = Chip8Interpreter::new_from_rom(rom)
+            .to_interpreter()
+            .with_frequency(600)
+            .with_event_sender(sender);
+
+        // Ten steps to a timer tick: it is set on the second and reaches zero on the twentieth
+        let buzzing = (1..=30)
+            .map(|_| {
+                int.step(no_keys());
+                *int.buzzer_active()
+            })
+            .collect::<Vec<_>>();
+        assert!(!buzzing[0]);
+        assert!(buzzing[1..19].iter().all(|&on| on));
+        assert!(buzzing[19..].iter().all(|&on| !on));
+        assert_eq!(*int.inner().sound_timer_register(), Datum(0));
+
+        let events = receiver.try_iter().collect::<Vec<_>>();
+        assert!(matches!(
+            events[..],
+            [
+                InterpreterEvent::BuzzerChanged(true),
+                InterpreterEvent::BuzzerChanged(false)
+            ]
+        ));
+    }
+
+    #[derive(Debug)]
+    struct ChangedRegisters(Arc<Mutex<Vec<u16>>>);
+
+    impl InterpreterHook<Chip8Interpreter> for ChangedRegisters {
+        fn after_step(&mut self, _: &mut Chip8Interpreter, frame: &mut FrameInfo) {
+            let changed =
+                <Self as HookInternalAccess<Chip8Interpreter>>::changed_registers(self, frame);
+            self.0.lock().unwrap().push(changed);
+        }
+    }
+
+    #[test]
+    fn test_changed_registers() {
+        let rom = ROM::from_instructions(&[
+            Instruction::Add(GeneralRegister::V2, 5),
+            Instruction::CopyRegToReg {
+                x: GeneralRegister::V3,
+                y: GeneralRegister::V4,
+            },
+        ])
+        .unwrap();
+        let changed = Arc::new(Mutex::new(vec![]));
+        let mut int = Interpreter::builder()
+            .extend_with(ChangedRegisters(changed.clone()))
+            .build(Chip8Interpreter::new_from_rom(rom));
+        int.step(no_keys());
+        int.step(no_keys());
+
+        assert_eq!(*changed.lock().unwrap(), [0b100, 0]);
+    }
+
+    #[test]
+    fn test_run_until() {
+        let rom = ROM::from_instructions(&[
+            Instruction::Add(GeneralRegister::V5, 1),
+            Instruction::Jump(Address::new(0x200)),
+        ])
+        .unwrap();
+        let mut int = Chip8Interpreter::new_from_rom(rom).to_interpreter();
+
+        let run = int.run_until(100, |int| int.view().registers[5] == Datum(3));
+        assert_eq!(run.reason, StopReason::Reached);
+        assert_eq!(run.cycles, 5);
+
+        let run = int.run_until(10, |int| int.view().program_counter == Address::new(0x300));
+        assert_eq!(run.reason, StopReason::CycleLimit);
+        assert_eq!(run.cycles, 10);
+    }
+
+    #[test]
+    fn test_skip_over_long_instruction() {
+        let rom = ROM::from_bytes(vec![
+            0x30, 0x00, // se v0, 0
+            0xF0, 0x00, 0x12, 0x34, // ld i, long 0x1234
+            0x40, 0x00, // sne v0, 0
+            0x00, 0xE0, // cls
+        ])
+        .unwrap();
+        let mut int = Chip8Interpreter::new_from_rom(rom).to_interpreter();
+        int.step(no_keys());
+        assert_eq!(int.inner().program_counter(), Address::new(0x206));
+        int.step(no_keys());
+        assert_eq!(int.inner().program_counter(), Address::new(0x208));
+    }
+
+    #[derive(Debug)]
+    struct Executed(Arc<Mutex<Vec<Instruction>>>);
+
+    impl InterpreterHook<Chip8Interpreter> for Executed {
+        fn after_step(&mut self, _: &mut Chip8Interpreter, frame: &mut FrameInfo) {
+            self.0.lock().unwrap().extend(frame.executed());
+        }
+    }
+
+    #[test]
+    fn test_start_at_subroutine() {
+        let rom = ROM::from_instructions(&[
+            Instruction::Call(Address::new(0x204)),
+            Instruction::Jump(Address::new(0x202)),
+            Instruction::LoadRegByte(GeneralRegister::V1, 7),
+            Instruction::Return,
+        ])
+        .unwrap();
+        let executed = Arc::new(Mutex::new(vec![]));
+        let mut int = Interpreter::builder()
+            .extend_with(Executed(executed.clone()))
+            .build(Chip8Interpreter::new_from_rom_starting_at(
+                rom,
+                Address::new(0x204),
+            ));
+        int.step(no_keys());
+
+        assert_eq!(
+            *executed.lock().unwrap(),
+            [Instruction::LoadRegByte(GeneralRegister::V1, 7)]
+        );
+        assert_eq!(int.inner().program_counter(), Address::new(0x206));
+    }
+
+    #[test]
+    fn test_start_at_entry() {
+        let source = ".entry main\nsub:\nld v1, 7\nret\nmain:\nld v2, 9\n";
+        let items = c8asm::parsing::parse(c8asm::tokenizing::tokenize(source).unwrap()).unwrap();
+        let (rom, debug) =
+            c8asm::compilation::compile_with_debug::<Chip8InstructionSet>(items).unwrap();
+        let mut int = Chip8Interpreter::new_from_assembled(rom, &debug).to_interpreter();
+        int.step(no_keys());
+
+        assert_eq!(int.inner().get_register(GeneralRegister::V2), Datum(9));
+        assert_eq!(int.inner().get_register(GeneralRegister::V1), Datum(0));
+    }
+
+    #[derive(Debug)]
+    struct MachineCalls(Arc<Mutex<Vec<Address>>>);
+
+    impl InterpreterHook<Chip8Interpreter> for MachineCalls {
+        fn machine_call(&mut self, _: &mut Chip8Interpreter, address: Address) {
+            self.0.lock().unwrap().push(address);
+        }
+    }
+
+    #[test]
+    fn test_sys_is_a_hookable_no_op() {
+        let rom = ROM::from_instructions(&[
+            Instruction::Sys(Address::new(0x123)),
+            Instruction::LoadRegByte(GeneralRegister::V0, 1),
+        ])
+        .unwrap();
+        let calls = Arc::new(Mutex::new(vec![]));
+        let mut int = Interpreter::builder()
+            .extend_with(MachineCalls(calls.clone()))
+            .build(Chip8Interpreter::new_from_rom(rom));
+        int.step(no_keys());
+        int.step(no_keys());
+
+        assert_eq!(*calls.lock().unwrap(), [Address::new(0x123)]);
+        assert_eq!(int.inner().get_register(GeneralRegister::V0), Datum(1));
+        assert_eq!(*int.state(), InterpreterState::Normal);
+    }
+
+    #[derive(Debug)]
+    struct CountSteps(Arc<Mutex<u32>>);
+
+    impl InterpreterHook<Chip8Interpreter> for CountSteps {
+        fn after_step(&mut self, _: &mut Chip8Interpreter, _: &mut FrameInfo) {
+            *self.0.lock().unwrap() += 1;
+        }
+    }
+
+    #[test]
+    fn test_toggle_hook() {
+        let rom = ROM::from_instructions(&[
+            Instruction::Add(GeneralRegister::V0, 1),
+            Instruction::Jump(Address::new(0x200)),
+        ])
+        .unwrap();
+        let steps = Arc::new(Mutex::new(0));
+        let mut int = Interpreter::builder()
+            .extend_with(CountSteps(steps.clone()))
+            .build(Chip8Interpreter::new_from_rom(rom));
+        assert_eq!(int.hook_count(), 1);
+        int.step(no_keys());
+
+        int.hook_mut(0).unwrap().disable();
+        assert!(!int.hooks().next().unwrap().is_enabled());
+        int.step(no_keys());
+        int.step(no_keys());
+        assert_eq!(*steps.lock().unwrap(), 1);
+
+        int.hook_mut(0).unwrap().enable();
+        int.step(no_keys());
+        assert_eq!(*steps.lock().unwrap(), 2);
+        assert!(int.hook_mut(1).is_none());
+    }
+
+    #[derive(Debug)]
+    struct RecordCalls(Arc<Mutex<Vec<&'static str>>>);
+
+    impl InterpreterHook<Chip8Interpreter> for RecordCalls {
+        fn pre_cycle(&mut self, _: &mut InterpreterState) {
+            self.0.lock().unwrap().push("pre_cycle");
+        }
+
+        fn before_step(&mut self, _: &mut Chip8Interpreter, _: &mut FrameInfo) {
+            self.0.lock().unwrap().push("before_step");
+        }
+
+        fn after_step(&mut self, _: &mut Chip8Interpreter, _: &mut FrameInfo) {
+            self.0.lock().unwrap().push("after_step");
+        }
+
+        fn post_cycle(&mut self, _: &mut InterpreterState) {
+            self.0.lock().unwrap().push("post_cycle");
+        }
+    }
+
+    #[test]
+    fn test_enabled_hook_forwards_each_method() {
+        let rom = ROM::from_instructions(&[
+            Instruction::Add(GeneralRegister::V0, 1),
+            Instruction::Jump(Address::new(0x200)),
+        ])
+        .unwrap();
+        let calls = Arc::new(Mutex::new(vec![]));
+        let mut int = Interpreter::builder()
+            .extend_with(EnabledHook::new(RecordCalls(calls.clone())))
+            .build(Chip8Interpreter::new_from_rom(rom));
+        for _ in 0..2 {
+            int.step(no_keys());
+        }
+        assert_eq!(
+            *calls.lock().unwrap(),
+            ["pre_cycle", "before_step", "after_step", "post_cycle"].repeat(2)
+        );
+    }
+
+    #[derive(Debug)]
+    struct KeyChanges(Arc<Mutex<Vec<(Keys, Keys)>>>);
+
+    impl InterpreterHook<Chip8Interpreter> for KeyChanges {
+        fn on_key_change(&mut self, pressed: Keys, released: Keys) {
+            self.0.lock().unwrap().push((pressed, released));
+        }
+    }
+
+    #[test]
+    fn test_key_change_edges() {
+        let rom = ROM::from_instructions(&[Instruction::Jump(Address::new(0x200))]).unwrap();
+        let changes = Arc::new(Mutex::new(vec![]));
+        let mut int = Interpreter::builder()
+            .extend_with(KeyChanges(changes.clone()))
+            .build(Chip8Interpreter::new_from_rom(rom));
+        let keys = |spec| Keys::from_spec(spec).unwrap();
+        for held in ["", "5", "5", "5,A", "A", ""] {
+            int.step(keys(held));
+        }
+        assert_eq!(
+            *changes.lock().unwrap(),
+            [
+                (keys("5"), keys("")),
+                (keys("A"), keys("")),
+                (keys(""), keys("5")),
+                (keys(""), keys("A")),
+            ]
+        );
+    }
+
+    #[derive(Debug)]
+    struct NopToCls;
+
+    impl InterpreterHook<Chip8Interpreter> for NopToCls {
+        fn on_decode(&mut self, instruction: Instruction) -> Option<Instruction> {
+            match instruction {
+                Instruction::Nop => Some(Instruction::ClearScreen),
+                other => Some(other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_on_decode_replaces_instruction() {
+        let rom = ROM::from_instructions(&[
+            Instruction::LoadImmediate(Address::new(FONT_START_ADDR as u16)),
+            Instruction::DisplaySprite {
+                x: GeneralRegister::V0,
+                y: GeneralRegister::V0,
+                number_of_bytes: 5,
+            },
+            Instruction::Nop,
+            Instruction::LoadRegByte(GeneralRegister::V0, 1),
+        ])
+        .unwrap();
+        let mut int = Interpreter::builder()
+            .extend_with(NopToCls)
+            .build(Chip8Interpreter::new_from_rom(rom));
+        for _ in 0..2 {
+            int.step(no_keys());
+        }
+        assert_ne!(*int.inner().display(), Display::blank());
+        int.step(no_keys());
+        assert_eq!(*int.inner().display(), Display::blank());
+    }
+
+    #[test]
+    fn test_draw_into_second_plane() {
+        let draw = Instruction::DisplaySprite {
+            x: GeneralRegister::V0,
+            y: GeneralRegister::V0,
+            number_of_bytes: 5,
+        };
+        let rom = ROM::from_instructions(&[
+            Instruction::LoadImmediate(Address::new(FONT_START_ADDR as u16)),
+            Instruction::SelectPlanes(0b10),
+            draw,
+            Instruction::SelectPlanes(0b11),
+            draw,
+        ])
+        .unwrap();
+        let mut int = Chip8Interpreter::new_from_rom(rom).to_interpreter();
+        for _ in 0..3 {
+            int.step(no_keys());
+        }
+        let display = *int.inner().display();
+        assert_eq!(display.raw(), Display::blank().raw());
+        assert_eq!(display.color_index(0, 0), Some(0b10));
+
+        // Both planes read their own 5 rows: "0" into the first, then "1" into the second
+        for _ in 0..2 {
+            int.step(no_keys());
+        }
+        let display = *int.inner().display();
+        assert_eq!(display.color_index(0, 0), Some(0b11));
+        assert_eq!(display.color_index(2, 0), Some(0b01));
+        assert_eq!(display.color_index(1, 1), Some(0b10));
+        assert_eq!(int.inner().get_register(GeneralRegister::VF), Datum(1));
+    }
+
+    #[test]
+    fn test_preloaded_state() {
+        let memory = ROM::from_instructions(&[Instruction::AddReg {
+            x: GeneralRegister::V0,
+            y: GeneralRegister::V2,
+        }])
+        .unwrap()
+        .to_memory();
+        let mut registers = [Datum(0); 16];
+        registers[..3].copy_from_slice(&[Datum(0xF0), Datum(0x01), Datum(0x20)]);
+        let mut int = Chip8Interpreter::new_from_memory(Memory::empty())
+            .with_memory(memory)
+            .with_registers(registers)
+            .to_interpreter();
+
+        int.step(no_keys());
+        let view = int.inner().view();
+        assert_eq!(view.registers[..3], [Datum(0x10), Datum(0x01), Datum(0x20)]);
+        assert_eq!(view.registers[0xF], Datum(1));
+    }
+
+    #[test]
+    fn test_display_wait_quirk() {
+        let draw = Instruction::DisplaySprite {
+            x: GeneralRegister::V0,
+            y: GeneralRegister::V0,
+            number_of_bytes: 1,
+        };
+        let rom = ROM::from_instructions(&[
+            Instruction::LoadRegByte(GeneralRegister::V0, 10),
+            Instruction::SetDelayTimer(GeneralRegister::V0),
+            draw,
+            Instruction::GetDelayTimer(GeneralRegister::V1),
+            draw,
+            Instruction::GetDelayTimer(GeneralRegister::V2),
+            Instruction::Jump(Address::new(0x20C)),
+        ])
+        .unwrap();
+        // The delay timer read after each draw tells whether a 60Hz tick came between them
+        let timers_after_draws = |display_wait| {
+            let mut int = Chip8Interpreter::new_from_rom(rom.clone())
+                .with_quirks(Quirks {
+                    display_wait,
+                    ..Quirks::default()
+                })
+                .to_interpreter()
+                .with_frequency(600);
+            for _ in 0..40 {
+                int.step(no_keys());
+            }
+            let view = int.inner().view();
+            (view.registers[1], view.registers[2])
+        };
+
+        let (first, second) = timers_after_draws(false);
+        assert_eq!(first, second);
+        let (first, second) = timers_after_draws(true);
+        assert!(second < first, "{:?} then {:?}", first, second);
+    }
 }
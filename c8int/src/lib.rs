@@ -1,11 +1,21 @@
 #![deny(unused_must_use, missing_debug_implementations)]
 #![warn(missing_copy_implementations)]
 
+mod divergence;
+pub use divergence::{first_divergence, Difference, Divergence};
+
 mod interpreter;
 pub use interpreter::Chip8Interpreter;
 
+mod quirks;
+pub use quirks::{Quirks, UnknownPreset};
+
+mod view;
+pub use view::StateView;
+
 pub(crate) mod prelude {
     pub(crate) use c8common::{
-        asm, memory::Memory, Address, Datum, Display, GeneralRegister, Instruction, RawInstruction,
+        asm, memory::Memory, Address, Datum, Display, GeneralRegister, Instruction,
+        InstructionDecodeError,
     };
 }
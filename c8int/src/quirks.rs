@@ -0,0 +1,106 @@
+use std::str::FromStr;
+use thiserror::Error;
+
+/// Behaviours that differ between historical interpreters
+/// The default matches this interpreter's original behaviour
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct Quirks {
+    /// `8xy6`/`8xyE` shift `Vy` into `Vx`, rather than shifting `Vx` in place
+    pub shift_uses_vy: bool,
+    /// `Fx55`/`Fx65` leave `I` pointing just past the last register stored or loaded
+    pub load_store_increments_i: bool,
+    /// `Bnnn` is read as `Bxnn`, jumping to `xnn + Vx`
+    pub jump_uses_vx: bool,
+    /// Sprites are cut off at the edges of the screen instead of wrapping around
+    pub clipping: bool,
+    /// `Fx1E` sets `VF` when `I` goes past `0x0FFF`, and wraps `I` back into 12 bits, as on the Amiga
+    pub add_i_sets_vf: bool,
+    /// `8xy1`/`8xy2`/`8xy3` reset `VF` to zero afterwards, as on the COSMAC VIP
+    pub logic_resets_vf: bool,
+    /// `Dxyn` waits for the next 60Hz vertical blank before the program continues, as on the
+    /// COSMAC VIP, so at most one sprite is drawn per frame
+    pub display_wait: bool,
+}
+
+impl Quirks {
+    pub fn cosmac_vip() -> Self {
+        Self {
+            shift_uses_vy: true,
+            load_store_increments_i: true,
+            jump_uses_vx: false,
+            clipping: true,
+            add_i_sets_vf: false,
+            logic_resets_vf: true,
+            display_wait: true,
+        }
+    }
+
+    pub fn super_chip() -> Self {
+        Self {
+            shift_uses_vy: false,
+            load_store_increments_i: false,
+            jump_uses_vx: true,
+            clipping: true,
+            add_i_sets_vf: false,
+            logic_resets_vf: false,
+            display_wait: false,
+        }
+    }
+
+    pub fn xo_chip() -> Self {
+        Self {
+            shift_uses_vy: true,
+            load_store_increments_i: true,
+            jump_uses_vx: false,
+            clipping: false,
+            add_i_sets_vf: false,
+            logic_resets_vf: false,
+            display_wait: false,
+        }
+    }
+}
+
+impl FromStr for Quirks {
+    type Err = UnknownPreset;
+
+    /// Parses the preset names `vip`, `schip` and `xo`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match &s.to_ascii_lowercase()[..] {
+            "vip" => Ok(Self::cosmac_vip()),
+            "schip" => Ok(Self::super_chip()),
+            "xo" => Ok(Self::xo_chip()),
+            _ => Err(UnknownPreset(s.to_string())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Error)]
+#[error("Unknown quirks preset '{0}', expected one of `vip`, `schip` or `xo`")]
+pub struct UnknownPreset(pub String);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_presets() {
+        let vip = Quirks::cosmac_vip();
+        assert!(vip.shift_uses_vy && vip.load_store_increments_i && vip.clipping);
+        assert!(vip.logic_resets_vf);
+        assert!(!vip.jump_uses_vx);
+
+        let schip = Quirks::super_chip();
+        assert!(schip.jump_uses_vx && schip.clipping);
+        assert!(!schip.shift_uses_vy && !schip.load_store_increments_i);
+
+        let xo = Quirks::xo_chip();
+        assert!(xo.shift_uses_vy && xo.load_store_increments_i);
+        assert!(!xo.jump_uses_vx && !xo.clipping);
+
+        assert_eq!("SCHIP".parse(), Ok(schip));
+        assert_eq!(
+            "chip48".parse::<Quirks>(),
+            Err(UnknownPreset("chip48".to_string()))
+        );
+    }
+}
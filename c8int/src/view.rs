@@ -0,0 +1,14 @@
+use crate::prelude::*;
+
+/// A read-only snapshot of everything a program can observe about the interpreter
+#[derive(Debug, Copy, Clone)]
+pub struct StateView<'a> {
+    pub program_counter: Address,
+    pub registers: [Datum; 16],
+    pub register_i: u16,
+    pub delay_timer: Datum,
+    pub sound_timer: Datum,
+    pub stack: &'a [Address],
+    pub memory: &'a Memory,
+    pub display: &'a Display,
+}
@@ -1,6 +1,7 @@
 use std::io::Write;
 
 use anyhow::Context;
+use c8common::display::{Frontend, LORES_HEIGHT, LORES_WIDTH};
 use c8common::pixel::Pixel;
 use c8common::Display;
 use pixels::{Pixels, SurfaceTexture};
@@ -26,20 +27,42 @@ pub fn init() -> anyhow::Result<(EventLoop<()>, Window, Pixels)> {
     let pixels = {
         let window_size = window.inner_size();
         let surface_texture = SurfaceTexture::new(window_size.width, window_size.height, &window);
-        Pixels::new(64, 32, surface_texture).context("Could not create pixels display surface")?
+        Pixels::new(LORES_WIDTH as u32, LORES_HEIGHT as u32, surface_texture)
+            .context("Could not create pixels display surface")?
     };
     Ok((event_loop, window, pixels))
 }
 
-pub fn update(pixels: &mut Pixels, buffer: &Display) -> anyhow::Result<()> {
-    let mut old_buf = pixels.get_frame();
-    for px in buffer.raw().concat() {
-        old_buf
-            .write_all(match px {
-                Pixel::Black => &[0_u8, 0_u8, 0_u8, 255_u8],
-                Pixel::White => &[255_u8, 255_u8, 255_u8, 255_u8],
-            })
-            .context("Error when writing data to internal pixels buffer")?
+/// The window's pixel buffer, resized to fit each frame
+#[derive(Debug)]
+pub struct Screen {
+    pub pixels: Pixels,
+    pub inverted: bool,
+}
+
+impl Frontend for Screen {
+    type Error = anyhow::Error;
+
+    fn present(&mut self, frame: &Display) -> anyhow::Result<()> {
+        //switching between lowres and hires changes how many pixels there are
+        if self.pixels.get_frame().len() != frame.width() * frame.height() * 4 {
+            self.pixels
+                .resize_buffer(frame.width() as u32, frame.height() as u32);
+        }
+        let mut old_buf = self.pixels.get_frame();
+        let raw = if self.inverted {
+            frame.raw_inverted().concat()
+        } else {
+            frame.raw().concat()
+        };
+        for px in raw {
+            old_buf
+                .write_all(match px {
+                    Pixel::Black => &[0_u8, 0_u8, 0_u8, 255_u8],
+                    Pixel::White => &[255_u8, 255_u8, 255_u8, 255_u8],
+                })
+                .context("Error when writing data to internal pixels buffer")?
+        }
+        Ok(())
     }
-    Ok(())
 }
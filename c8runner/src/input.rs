@@ -1,4 +1,7 @@
+use anyhow::{bail, Context};
 use c8common::key::Keys;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
 use winit::event::VirtualKeyCode;
 use winit_input_helper::WinitInputHelper;
 use VirtualKeyCode::*;
@@ -26,6 +29,91 @@ const KEYMAP: [VirtualKeyCode; 16] = [
     V,    //F
 ];
 
+/// The host keys a keymap file may bind
+const HOST_KEYS: [VirtualKeyCode; 50] = [
+    Key1, Key2, Key3, Key4, Key5, Key6, Key7, Key8, Key9, Key0, A, B, C, D, E, F, G, H, I, J, K, L,
+    M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z, Up, Down, Left, Right, Space, Return, Back, Tab,
+    LShift, RShift, LControl, RControl, LAlt, RAlt,
+];
+
 pub fn key_state(input: &WinitInputHelper) -> Keys {
-    Keys::from_raw(KEYMAP.map(|k| input.key_held(k)))
+    KeyMap::default().key_state(input)
+}
+
+/// Which host key presses each CHIP-8 key, if any, indexed by nibble
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct KeyMap([Option<VirtualKeyCode>; 16]);
+
+impl Default for KeyMap {
+    fn default() -> Self {
+        Self(KEYMAP.map(Some))
+    }
+}
+
+impl KeyMap {
+    /// Reads a TOML table of host key names (as spelled in winit's `VirtualKeyCode`) to CHIP-8
+    /// keys, like `Up = 0x5`. Any CHIP-8 key left out keeps its default binding, unless its host
+    /// key was bound to another, in which case it is left unbound.
+    pub fn from_toml(source: &str) -> anyhow::Result<Self> {
+        let table: BTreeMap<String, u8> = toml::from_str(source).context("Invalid keymap.")?;
+        let mut map = Self::default();
+        let mut bound_by = HashMap::new();
+        for (name, key) in table {
+            if key > 0xF {
+                bail!("Key {name} is bound to {key:#X}, but CHIP-8 keys only go up to 0xF.");
+            }
+            if let Some(other) = bound_by.insert(key, name.clone()) {
+                bail!("Both {other} and {name} are bound to {key:#X}.");
+            }
+            let host = HOST_KEYS
+                .iter()
+                .copied()
+                .find(|host| format!("{host:?}") == name)
+                .with_context(|| format!("Unknown host key {name}."))?;
+            for binding in &mut map.0 {
+                if *binding == Some(host) {
+                    *binding = None;
+                }
+            }
+            map.0[key as usize] = Some(host);
+        }
+        Ok(map)
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let source = std::fs::read_to_string(path)
+            .with_context(|| format!("Could not read keymap {}.", path.display()))?;
+        Self::from_toml(&source)
+    }
+
+    pub fn key_state(&self, input: &WinitInputHelper) -> Keys {
+        Keys::from_raw(self.0.map(|k| k.is_some_and(|k| input.key_held(k))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keymap_from_toml() {
+        let map = KeyMap::from_toml("Up = 0x5\nSpace = 6\n").unwrap();
+        assert_eq!(map.0[0x5], Some(Up));
+        assert_eq!(map.0[0x6], Some(Space));
+        assert_eq!(map.0[0x0], Some(X));
+
+        assert!(KeyMap::from_toml("Up = 0x10").is_err());
+        assert!(KeyMap::from_toml("NotAKey = 1").is_err());
+    }
+
+    #[test]
+    fn test_keymap_rebinding() {
+        assert!(KeyMap::from_toml("Up = 0x5\nDown = 0x5\n").is_err());
+
+        let map = KeyMap::from_toml("X = 0x5\n").unwrap();
+        assert_eq!(map.0[0x5], Some(X));
+        assert_eq!(map.0[0x0], None);
+        assert_eq!(map.0.iter().filter(|&&k| k == Some(X)).count(), 1);
+    }
 }
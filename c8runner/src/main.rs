@@ -2,9 +2,15 @@
 #![warn(missing_copy_implementations)]
 
 use c8common::control::execute::Interpreter;
-use c8runner::run::run;
+use c8hooks::execution_dumper::ExecutionDumper;
+use c8hooks::recorder::Recorder;
+use c8hooks::tracer::Tracer;
+use c8int::{Chip8Interpreter, Quirks};
+use c8runner::input::KeyMap;
+use c8runner::run::{run, RunOptions};
 use clap::Parser;
 use simplelog::{ColorChoice, ConfigBuilder, LevelFilter, TermLogger, TerminalMode};
+use std::path::PathBuf;
 use std::str::FromStr;
 
 #[derive(Parser, Debug)]
@@ -16,6 +22,24 @@ struct Args {
     frequency_scale: Option<f32>,
     #[arg(long = "log", value_parser = <LevelFilter as FromStr>::from_str, default_value_t = LevelFilter::Trace)]
     log_level: LevelFilter,
+    /// Draw black pixels on a white background
+    #[arg(long = "inverted")]
+    inverted: bool,
+    /// Compatibility preset: `vip`, `schip` or `xo`
+    #[arg(long = "quirks", value_parser = <Quirks as FromStr>::from_str)]
+    quirks: Option<Quirks>,
+    /// TOML file binding host key names to CHIP-8 keys, like `Up = 0x5`
+    #[arg(long = "keymap")]
+    keymap: Option<PathBuf>,
+    /// Write a full dump of every step to this file
+    #[arg(long = "dump")]
+    dump: Option<PathBuf>,
+    /// Save every frame as an image in this folder
+    #[arg(long = "record")]
+    record: Option<PathBuf>,
+    /// Log each executed instruction at trace level
+    #[arg(long = "trace")]
+    trace: bool,
 }
 
 fn main() {
@@ -24,6 +48,12 @@ fn main() {
         frequency,
         frequency_scale: simulated_frequency,
         log_level,
+        inverted,
+        quirks,
+        keymap,
+        dump,
+        record,
+        trace,
     } = Args::parse();
 
     TermLogger::init(
@@ -39,7 +69,14 @@ fn main() {
         ColorChoice::Always,
     ).expect("could not set up logging!");
 
-    let int = c8int::Chip8Interpreter::new_from_file(rom_path);
+    let keymap = keymap
+        .map(KeyMap::from_file)
+        .transpose()
+        .expect("could not load keymap!")
+        .unwrap_or_default();
+
+    let int = Chip8Interpreter::new_from_file(rom_path)
+        .with_quirks(quirks.unwrap_or_default());
     // let int = c8int::Chip8Interpreter::new_assembled_save("test_rng.ch8", |asm| {
     //     asm
     //         .rng(GeneralRegister::V0, 0xFF)
@@ -49,10 +86,21 @@ fn main() {
     //
     // int.memory().save(std::fs::File::create("roms/test_rng.mem").unwrap());
 
-    run(Interpreter::builder()
-        // .extend_with(c8hooks::execution_dumper::ExecutionDumper::dump_to("exec.dump").unwrap())
-        // .extend_with(c8hooks::recorder::Recorder::images_to_folder("output"))
+    let mut builder = Interpreter::<Chip8Interpreter>::builder();
+    if let Some(dump) = dump {
+        let dumper = ExecutionDumper::dump_to(dump).expect("could not create dump file!");
+        builder = builder.extend_with(dumper);
+    }
+    if let Some(record) = record {
+        builder = builder.extend_with(Recorder::images_to_folder(record));
+    }
+    if trace {
+        builder = builder.extend_with(Tracer::new());
+    }
+
+    run(builder
         .build(int)
         .with_frequency(frequency)
-        .with_simulated_frequency(simulated_frequency));
+        .with_simulated_frequency(simulated_frequency),
+        RunOptions { inverted, keymap });
 }
@@ -1,7 +1,9 @@
-use crate::{display, input, sound};
+use crate::input::KeyMap;
+use crate::{display, sound};
 use anyhow::Context;
 use c8common::control::execute::Interpreter;
 use c8common::control::ControlledInterpreter;
+use c8common::display::Frontend;
 use c8common::key::Keys;
 use c8common::Display;
 use crossbeam::atomic::AtomicCell;
@@ -15,17 +17,30 @@ use winit::event::{Event, VirtualKeyCode};
 use winit::event_loop::ControlFlow;
 use winit_input_helper::WinitInputHelper;
 
+/// Presentation and input settings for the window, which don't affect emulation.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct RunOptions {
+    /// Draw black pixels on a white background.
+    pub inverted: bool,
+    /// Host keys for each CHIP-8 key.
+    pub keymap: KeyMap,
+}
+
 /// Starts the interpreter, blocking the current thread and running until killed.
 /// Windowing, graphics, sound, and timing are all handled within this method.
-pub fn run<I: ControlledInterpreter>(mut interpreter: Interpreter<I>) -> !
+pub fn run<I: ControlledInterpreter>(mut interpreter: Interpreter<I>, options: RunOptions) -> !
 where
     I: Send + 'static,
 {
     //init display subsystem
     log::info!("Initalising display components...");
-    let (event_loop, window, mut pixels) = display::init()
+    let (event_loop, window, pixels) = display::init()
         .context("Could not initialise display subsystem.")
         .unwrap(); //failure to init display is fatal, so panic.
+    let mut screen = display::Screen {
+        pixels,
+        inverted: options.inverted,
+    };
 
     //init input subsystem
     log::info!("Initalising input components...");
@@ -99,14 +114,15 @@ where
 
         //only redraw if there was an update
         if !new_frame.1 {
-            display::update(&mut pixels, &new_frame.0)
+            screen
+                .present(&new_frame.0)
                 .context("Failed to update display")
                 .unwrap(); //panic if failed to update display for whatever reason
         }
 
         //if the OS requested a redraw of the window
         if let Event::RedrawRequested(_) = event {
-            if let Err(e) = pixels.render() {
+            if let Err(e) = screen.pixels.render() {
                 panic!("Pixels rendering failure, caused by: {:?}", e.source());
             }
         }
@@ -119,11 +135,11 @@ where
                 return;
             }
             //handle keyboard input to emulator
-            input_buffer.swap(input::key_state(&input));
+            input_buffer.swap(options.keymap.key_state(&input));
 
             // Resize the window
             if let Some(size) = input.window_resized() {
-                pixels.resize_surface(size.width, size.height);
+                screen.pixels.resize_surface(size.width, size.height);
             }
         }
         window.request_redraw();
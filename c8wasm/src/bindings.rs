@@ -0,0 +1,24 @@
+use crate::Emulator;
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+#[derive(Debug)]
+pub struct Chip8(Emulator);
+
+#[wasm_bindgen]
+impl Chip8 {
+    #[wasm_bindgen(constructor)]
+    pub fn new(rom: &[u8]) -> Result<Chip8, JsValue> {
+        Emulator::new(rom)
+            .map(Self)
+            .map_err(|e| JsValue::from_str(&format!("Could not load ROM: {:?}", e)))
+    }
+
+    pub fn set_keys(&mut self, keys: u16) {
+        self.0.set_keys(keys)
+    }
+
+    pub fn step(&mut self, keys: u16) -> Option<Vec<u8>> {
+        self.0.step(keys)
+    }
+}
@@ -0,0 +1,89 @@
+#![deny(missing_debug_implementations, unused_must_use)]
+#![warn(missing_copy_implementations)]
+
+use c8common::asm::{LoadError, ROM};
+use c8common::control::execute::Interpreter;
+use c8common::control::ControlledToInterpreter;
+use c8common::key::Keys;
+use c8common::pixel::Pixel;
+use c8common::Display;
+use c8int::Chip8Interpreter;
+
+#[cfg(target_arch = "wasm32")]
+mod bindings;
+
+const STEP_FREQUENCY: u32 = 512;
+
+/// Interpreter façade that only exchanges plain integers and bytes, so it can be exported to JS
+#[derive(Debug)]
+pub struct Emulator {
+    interpreter: Interpreter<Chip8Interpreter>,
+    keys: Keys,
+}
+
+impl Emulator {
+    pub fn new(rom: &[u8]) -> Result<Self, LoadError> {
+        let rom = ROM::from_bytes(rom.to_vec())?;
+        Ok(Self {
+            interpreter: Chip8Interpreter::new_from_rom(rom)
+                .to_interpreter()
+                .with_frequency(STEP_FREQUENCY),
+            keys: Keys::from_raw([false; 16]),
+        })
+    }
+
+    /// Bit `n` of the mask is set while key `n` is held down
+    pub fn set_keys(&mut self, keys: u16) {
+        self.keys = Keys::from_raw(std::array::from_fn(|i| keys & (1 << i) != 0));
+    }
+
+    /// Returns the new framebuffer if the screen changed during this step
+    pub fn step(&mut self, keys: u16) -> Option<Vec<u8>> {
+        self.set_keys(keys);
+        self.interpreter
+            .step(self.keys)
+            .map(|display| Self::framebuffer(&display))
+    }
+
+    /// One byte per pixel, row by row, `1` for lit pixels and `0` otherwise.
+    /// Each row is `display.width()` pixels long, so a hires frame is four times the size.
+    pub fn framebuffer(display: &Display) -> Vec<u8> {
+        display
+            .raw()
+            .concat()
+            .into_iter()
+            .map(|pixel| u8::from(pixel == Pixel::White))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_step_returns_framebuffer() {
+        // ld v0, 0x0A; ld f, v0; drw v1, v1, 5; jp 0x206
+        let rom = [0x60, 0x0A, 0xF0, 0x29, 0xD1, 0x15, 0x12, 0x06];
+        let mut emulator = Emulator::new(&rom).unwrap();
+
+        assert_eq!(emulator.step(0), None);
+        assert_eq!(emulator.step(0), None);
+        let frame = emulator.step(0).expect("the sprite was drawn");
+        assert_eq!(frame.len(), 64 * 32);
+        // Top row of the "A" glyph is 0xF0
+        assert_eq!(&frame[..8], &[1, 1, 1, 1, 0, 0, 0, 0]);
+        assert_eq!(&frame[64..72], &[1, 0, 0, 1, 0, 0, 0, 0]);
+        assert_eq!(frame.iter().filter(|&&p| p == 1).count(), 14);
+        assert_eq!(emulator.step(0), None);
+    }
+
+    #[test]
+    fn test_set_keys_mask() {
+        let mut emulator = Emulator::new(&[]).unwrap();
+        emulator.set_keys(0b1000_0000_0000_0010);
+        let mut expected = Keys::from_number(1);
+        expected |= Keys::from_number(0xF);
+        assert_eq!(emulator.keys, expected);
+    }
+}